@@ -0,0 +1,141 @@
+use crate::{
+    database::{
+        access::{Rule, Subject},
+        storage::Backend,
+    },
+    Config, Database,
+};
+use anyhow::Result;
+use parking_lot::RwLock;
+use std::{collections::HashMap, sync::Arc};
+
+pub struct AccessControl {
+    owner: Option<i64>,
+    fixed: HashMap<Subject, Rule>,
+    rules: RwLock<HashMap<Subject, Rule>>,
+    database: Arc<Database>,
+    is_persistent: bool,
+}
+
+pub enum RuleChange {
+    Applied,
+    Fixed,
+    NotPersistent,
+}
+
+impl AccessControl {
+    pub fn new(config: &Config, database: Arc<Database>) -> Result<Self> {
+        let fixed = config
+            .allowed_chats
+            .iter()
+            .map(|id| (Subject::Chat(*id), Rule::Allow))
+            .chain(
+                config
+                    .allowed_users
+                    .iter()
+                    .map(|id| (Subject::User(*id), Rule::Allow)),
+            )
+            .chain(
+                config
+                    .denied_chats
+                    .iter()
+                    .map(|id| (Subject::Chat(*id), Rule::Deny)),
+            )
+            .chain(
+                config
+                    .denied_users
+                    .iter()
+                    .map(|id| (Subject::User(*id), Rule::Deny)),
+            )
+            .collect();
+
+        let rules = database
+            .access
+            .lock()
+            .entries()?
+            .into_iter()
+            .map(|entry| (entry.key, entry.value))
+            .collect();
+
+        Ok(Self {
+            owner: config.owner,
+            fixed,
+            rules: RwLock::new(rules),
+            database,
+            is_persistent: !matches!(config.storage, Backend::Memory)
+                || config.state_file.is_some(),
+        })
+    }
+
+    pub fn is_allowed(&self, chat_id: i64, user_id: Option<i64>) -> bool {
+        if user_id.is_some() && user_id == self.owner {
+            return true;
+        }
+
+        let rules = self.rules.read();
+        let get_rule = |subject| self.fixed.get(&subject).or_else(|| rules.get(&subject));
+
+        let chat = get_rule(Subject::Chat(chat_id));
+        let user = user_id.and_then(|id| get_rule(Subject::User(id)));
+
+        if chat == Some(&Rule::Deny) || user == Some(&Rule::Deny) {
+            return false;
+        }
+
+        let has_allow_rules = self
+            .fixed
+            .values()
+            .chain(rules.values())
+            .any(|rule| *rule == Rule::Allow);
+
+        !has_allow_rules || chat == Some(&Rule::Allow) || user == Some(&Rule::Allow)
+    }
+
+    pub fn is_chat_allowed(&self, chat_id: i64) -> bool {
+        let rules = self.rules.read();
+        let subject = Subject::Chat(chat_id);
+
+        match self.fixed.get(&subject).or_else(|| rules.get(&subject)) {
+            Some(Rule::Allow) => true,
+            Some(Rule::Deny) => false,
+            None => !self
+                .fixed
+                .iter()
+                .chain(rules.iter())
+                .any(|(subject, rule)| matches!(subject, Subject::Chat(_)) && *rule == Rule::Allow),
+        }
+    }
+
+    pub fn set_rule(&self, subject: Subject, rule: Option<Rule>) -> Result<RuleChange> {
+        if self.fixed.contains_key(&subject) {
+            return Ok(RuleChange::Fixed);
+        }
+
+        if !self.is_persistent {
+            return Ok(RuleChange::NotPersistent);
+        }
+
+        let mut rules = self.rules.write();
+        let pool = self.database.access.lock();
+
+        if let Some(rule) = rule {
+            pool.insert(subject, rule)?;
+            rules.insert(subject, rule);
+        } else {
+            pool.remove(&subject)?;
+            rules.remove(&subject);
+        }
+
+        Ok(RuleChange::Applied)
+    }
+
+    pub fn list_rules(&self) -> Vec<(Subject, Rule, bool)> {
+        let rules = self.rules.read();
+
+        self.fixed
+            .iter()
+            .map(|(subject, rule)| (*subject, *rule, true))
+            .chain(rules.iter().map(|(subject, rule)| (*subject, *rule, false)))
+            .collect()
+    }
+}
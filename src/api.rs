@@ -0,0 +1,317 @@
+use crate::{health::Health, metrics::Metrics};
+use anyhow::anyhow;
+use futures_util::StreamExt;
+use rand::{rngs::OsRng, Rng};
+use reqwest::{
+    multipart::{Form, Part},
+    Client, RequestBuilder,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tgbot::{
+    methods::{
+        AnswerCallbackQuery, DeleteWebhook, EditMessageText, GetChatMember, GetFile, GetMe,
+        LeaveChat, Method, SendDice, SendMessage, SetMyCommands,
+    },
+    types::{Response, Update},
+    Api as BotApi, ExecuteError,
+};
+use tokio::{fs, time};
+use tracing::{error, warn};
+
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+const BACKOFF_MAX: Duration = Duration::from_secs(10);
+
+const DEFAULT_API_URL: &str = "https://api.telegram.org";
+
+pub struct Api {
+    inner: BotApi,
+    client: Client,
+    method_url: String,
+    local_files: bool,
+    metrics: Arc<Metrics>,
+    health: Arc<Health>,
+    retry_budget: Duration,
+}
+
+pub trait ApiMethod: Method {
+    const NAME: &'static str;
+    const IS_IDEMPOTENT: bool;
+
+    fn get_params(&self) -> serde_json::Result<Value>;
+}
+
+macro_rules! impl_api_method {
+    ($($method:ty => $name:literal, $is_idempotent:literal;)*) => {
+        $(
+            impl ApiMethod for $method {
+                const NAME: &'static str = $name;
+                const IS_IDEMPOTENT: bool = $is_idempotent;
+
+                fn get_params(&self) -> serde_json::Result<Value> {
+                    serde_json::to_value(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_api_method! {
+    AnswerCallbackQuery => "answerCallbackQuery", true;
+    DeleteWebhook => "deleteWebhook", true;
+    EditMessageText => "editMessageText", true;
+    GetChatMember => "getChatMember", true;
+    GetFile => "getFile", true;
+    LeaveChat => "leaveChat", true;
+    SendDice => "sendDice", false;
+    SendMessage => "sendMessage", false;
+    SetMyCommands => "setMyCommands", true;
+}
+
+impl ApiMethod for GetMe {
+    const NAME: &'static str = "getMe";
+    const IS_IDEMPOTENT: bool = true;
+
+    fn get_params(&self) -> serde_json::Result<Value> {
+        Ok(json!({}))
+    }
+}
+
+pub struct SetWebhook {
+    pub url: String,
+    pub max_connections: u8,
+    pub allowed_updates: &'static [&'static str],
+    pub certificate: Option<PathBuf>,
+    pub secret_token: String,
+}
+
+impl Api {
+    pub fn new(
+        client: Client,
+        api_url: Option<&str>,
+        token: &str,
+        metrics: Arc<Metrics>,
+        health: Arc<Health>,
+        retry_budget: Duration,
+    ) -> Self {
+        let local_files = api_url.is_some();
+        let api_url = api_url.unwrap_or(DEFAULT_API_URL);
+
+        Self {
+            inner: BotApi::with_client(client.clone(), token).with_host(api_url),
+            client,
+            method_url: format!("{api_url}/bot{token}"),
+            local_files,
+            metrics,
+            health,
+            retry_budget,
+        }
+    }
+
+    pub async fn execute<M>(&self, method: M) -> Result<M::Response, ExecuteError>
+    where
+        M: ApiMethod,
+        M::Response: DeserializeOwned,
+    {
+        let params = method.get_params()?;
+
+        self.retry(M::NAME, M::IS_IDEMPOTENT, || {
+            let req = self
+                .client
+                .post(format!("{}/{}", self.method_url, M::NAME))
+                .json(&params);
+
+            self.observe(M::NAME, send(req))
+        })
+        .await
+    }
+
+    async fn retry<T, F, Fut>(
+        &self,
+        method: &'static str,
+        is_idempotent: bool,
+        mut f: F,
+    ) -> Result<T, ExecuteError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ExecuteError>>,
+    {
+        let mut waited = Duration::ZERO;
+        let mut attempt = 0;
+
+        loop {
+            let err = match f().await {
+                Ok(res) => return Ok(res),
+                Err(err) => err,
+            };
+
+            let delay = match get_retry_delay(&err, attempt, is_idempotent) {
+                Some(delay) => delay,
+                None => return Err(err),
+            };
+
+            if waited + delay > self.retry_budget {
+                error!(
+                    method,
+                    attempts = attempt + 1,
+                    error = %err,
+                    "Giving up on API request"
+                );
+
+                return Err(err);
+            }
+
+            warn!(
+                method,
+                attempt = attempt + 1,
+                delay_ms = delay.as_millis() as u64,
+                error = %err,
+                "Retrying API request"
+            );
+
+            time::sleep(delay).await;
+
+            waited += delay;
+            attempt += 1;
+        }
+    }
+
+    async fn observe<T>(
+        &self,
+        method: &'static str,
+        res: impl Future<Output = Result<T, ExecuteError>>,
+    ) -> Result<T, ExecuteError> {
+        let start = Instant::now();
+        let res = res.await;
+
+        if res.is_ok() {
+            self.health.api_succeeded();
+        }
+
+        self.metrics
+            .api_requests
+            .with_label_values(&[method, if res.is_ok() { "ok" } else { "error" }])
+            .observe(start.elapsed().as_secs_f64());
+
+        res
+    }
+
+    pub async fn get_updates(
+        &self,
+        offset: i64,
+        timeout: Duration,
+        allowed_updates: &[&str],
+    ) -> Result<Vec<Update>, ExecuteError> {
+        let params = json!({
+            "offset": offset,
+            "timeout": timeout.as_secs(),
+            "allowed_updates": allowed_updates,
+        });
+
+        self.retry("getUpdates", true, || {
+            let req = self
+                .client
+                .post(format!("{}/getUpdates", self.method_url))
+                .json(&params);
+
+            self.observe("getUpdates", send(req))
+        })
+        .await
+    }
+
+    pub async fn set_webhook(&self, webhook: SetWebhook) -> anyhow::Result<()> {
+        let allowed_updates = serde_json::to_string(&webhook.allowed_updates)?;
+
+        let cert = if let Some(path) = &webhook.certificate {
+            let cert = fs::read(path)
+                .await
+                .map_err(|err| anyhow!("Failed to read {}: {err}", path.display()))?;
+
+            Some(cert)
+        } else {
+            None
+        };
+
+        let build_form = || {
+            let mut form = Form::new()
+                .text("url", webhook.url.clone())
+                .text("max_connections", webhook.max_connections.to_string())
+                .text("allowed_updates", allowed_updates.clone())
+                .text("secret_token", webhook.secret_token.clone());
+
+            if let Some(cert) = &cert {
+                form = form.part(
+                    "certificate",
+                    Part::bytes(cert.clone()).file_name("cert.pem"),
+                );
+            }
+
+            form
+        };
+
+        self.retry("setWebhook", true, || {
+            let req = self
+                .client
+                .post(format!("{}/setWebhook", self.method_url))
+                .multipart(build_form());
+
+            self.observe("setWebhook", send::<bool>(req))
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn download_file(&self, file_path: &str) -> anyhow::Result<Vec<u8>> {
+        if self.local_files && Path::new(file_path).is_absolute() {
+            return Ok(fs::read(file_path).await?);
+        }
+
+        let mut stream = self.inner.download_file(file_path).await?;
+        let mut file = Vec::new();
+
+        while let Some(chunk) = stream.next().await {
+            file.extend_from_slice(&chunk?);
+        }
+
+        Ok(file)
+    }
+}
+
+async fn send<T>(req: RequestBuilder) -> Result<T, ExecuteError>
+where
+    T: DeserializeOwned,
+{
+    let res = req.send().await?.json::<Response<T>>().await?;
+    Ok(res.into_result()?)
+}
+
+fn get_retry_delay(err: &ExecuteError, attempt: u32, is_idempotent: bool) -> Option<Duration> {
+    match err {
+        ExecuteError::Response(err) => {
+            if let Some(secs) = err.retry_after() {
+                Some(Duration::from_secs(secs))
+            } else if is_idempotent && err.error_code().map_or(false, |code| code >= 500) {
+                Some(get_backoff(attempt))
+            } else {
+                None
+            }
+        }
+        ExecuteError::Http(err) if err.is_connect() => Some(get_backoff(attempt)),
+        ExecuteError::Http(err) if is_idempotent && (err.is_timeout() || err.is_request()) => {
+            Some(get_backoff(attempt))
+        }
+        _ => None,
+    }
+}
+
+fn get_backoff(attempt: u32) -> Duration {
+    let max = (BACKOFF_BASE * 2u32.pow(attempt.min(5))).min(BACKOFF_MAX);
+    Duration::from_millis(OsRng.gen_range(0..=max.as_millis() as u64))
+}
@@ -1,37 +1,202 @@
-use crate::{Config, Database, Handler};
-use anyhow::Result;
+use crate::{
+    access::AccessControl,
+    api::{Api, SetWebhook},
+    database::ExpiredSessionReceiver,
+    health::{Health, HEARTBEAT_PERIOD},
+    metrics::Metrics,
+    monitor::Monitor,
+    outbox::Outbox,
+    webhook, Config, Database, Handler,
+};
+use anyhow::{anyhow, Result};
 use reqwest::Client;
-use std::sync::Arc;
-use tgbot::{longpoll::LongPoll, methods::GetMe, types::Me, webhook, Api};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tgbot::{
+    methods::{DeleteWebhook, GetMe},
+    types::Me,
+    UpdateHandler,
+};
+use tokio::time;
+use tracing::{info, warn};
+
+const POLL_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_ERROR_TIMEOUT: Duration = Duration::from_secs(5);
+
+const ALLOWED_UPDATES: &[&str] = &["message", "callback_query", "my_chat_member"];
+
+pub async fn run(
+    config: Config,
+    database: Arc<Database>,
+    expired: ExpiredSessionReceiver,
+    shutdown: impl Future<Output = Result<()>>,
+) -> Result<()> {
+    Handler::check_command_texts()?;
 
-pub async fn run(config: Config, database: Arc<Database>) -> Result<()> {
     let http_cli = {
         let mut builder = Client::builder();
 
-        if let Some(proxy) = config.proxy {
+        if let Some(proxy) = config.proxy.clone() {
             builder = builder.proxy(proxy);
         }
 
         builder.build()?
     };
 
-    let api = Api::with_client(http_cli, config.token);
+    let metrics = Arc::new(Metrics::new()?);
+    let health = Arc::new(Health::new(&config));
+
+    let api = Arc::new(Api::new(
+        http_cli,
+        config.api_url.as_deref(),
+        &config.token,
+        metrics.clone(),
+        health.clone(),
+        config.api_retry_budget,
+    ));
+
+    if let Some(metrics_port) = config.metrics_port {
+        let monitor = Monitor {
+            metrics: metrics.clone(),
+            health,
+            database: database.clone(),
+        };
+
+        tokio::spawn(monitor.serve(metrics_port)?);
+        info!(port = metrics_port, "Serving metrics and health checks");
+    }
 
     let Me { username, .. } = api.execute(GetMe).await?;
     let username = format!("@{username}");
 
-    if let Some(webhook_port) = config.webhook_port {
-        webhook::run_server(
-            ([0, 0, 0, 0], webhook_port),
-            "/",
-            Handler::new(database, api, username),
-        )
-        .await?;
+    info!(%username, "Logged in");
+
+    let (outbox, dispatcher) = Outbox::new(api.clone(), config.send_rates);
+    tokio::spawn(dispatcher);
+
+    let access = Arc::new(AccessControl::new(&config, database.clone())?);
+
+    let config = Arc::new(config);
+    let handler = Handler::new(
+        config.clone(),
+        database,
+        api.clone(),
+        metrics,
+        outbox,
+        access,
+        username,
+    );
+
+    tokio::spawn(handler.clone().handle_expired_sessions(expired));
+
+    if let Err(err) = handler.set_my_commands().await {
+        warn!(error = %format!("{err:#}"), "Failed to set bot commands");
+    }
+
+    let offset = AtomicI64::new(0);
+
+    tokio::select! {
+        res = serve(&config, api.clone(), handler.clone(), &offset) => res?,
+        res = shutdown => res?,
+    }
+
+    info!("Shutting down");
+
+    let offset = offset.load(Ordering::SeqCst);
+
+    if offset > 0 {
+        if let Err(err) = api
+            .get_updates(offset + 1, Duration::ZERO, ALLOWED_UPDATES)
+            .await
+        {
+            warn!(error = %err, "Failed to confirm handled updates");
+        }
+    }
+
+    if time::timeout(config.shutdown_timeout, handler.wait_in_flight())
+        .await
+        .is_err()
+    {
+        warn!("Timed out waiting for in-flight updates");
+    }
+
+    Ok(())
+}
+
+async fn serve(config: &Config, api: Arc<Api>, handler: Handler, offset: &AtomicI64) -> Result<()> {
+    if let Some(listen) = &config.webhook_listen {
+        let secret_token = config
+            .webhook_secret_token
+            .clone()
+            .unwrap_or_else(webhook::generate_secret_token);
+
+        if let Some(url) = &config.webhook_url {
+            let set_webhook = SetWebhook {
+                url: url.clone(),
+                max_connections: config.webhook_max_connections,
+                allowed_updates: ALLOWED_UPDATES,
+                certificate: config.webhook_self_signed_cert.clone(),
+                secret_token: secret_token.clone(),
+            };
+
+            api.set_webhook(set_webhook)
+                .await
+                .map_err(|err| anyhow!("Failed to set webhook: {err:#}"))?;
+
+            info!(%url, "Webhook set");
+        }
+
+        info!(%listen, path = %config.webhook_path, "Running in webhook mode");
+        tokio::spawn(heartbeat(api));
+        webhook::run_server(config, listen, secret_token, handler).await?;
     } else {
-        LongPoll::new(api.clone(), Handler::new(database, api, username))
-            .run()
-            .await;
+        api.execute(DeleteWebhook::default())
+            .await
+            .map_err(|err| anyhow!("Failed to delete webhook: {err}"))?;
+
+        info!("Running in long polling mode");
+        poll(&api, handler, offset).await;
     }
 
     Ok(())
 }
+
+async fn poll(api: &Api, handler: Handler, offset: &AtomicI64) {
+    loop {
+        let next_offset = offset.load(Ordering::SeqCst) + 1;
+
+        match api
+            .get_updates(next_offset, POLL_TIMEOUT, ALLOWED_UPDATES)
+            .await
+        {
+            Ok(updates) => {
+                for update in updates {
+                    offset.fetch_max(update.id, Ordering::SeqCst);
+                    tokio::spawn(handler.handle(update));
+                }
+            }
+            Err(err) => {
+                warn!(error = %err, "Failed to get updates");
+                time::sleep(POLL_ERROR_TIMEOUT).await;
+            }
+        }
+    }
+}
+
+async fn heartbeat(api: Arc<Api>) {
+    let mut interval = time::interval(HEARTBEAT_PERIOD);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = api.execute(GetMe).await {
+            warn!(error = %err, "Heartbeat failed");
+        }
+    }
+}
@@ -1,13 +1,70 @@
+use crate::{
+    database::{ocr::Language, storage::Backend},
+    i18n::Locale,
+    webhook::ListenAddr,
+    Handler, RateLimits, SendRates, SessionLifetimes,
+};
 use anyhow::{anyhow, bail, Result};
-use getopts::Options;
-use reqwest::Proxy;
+use getopts::{Matches, Options};
+use reqwest::{Proxy, Url};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    env::{self, VarError},
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs,
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+use tracing_subscriber::filter::LevelFilter;
 
 pub struct Config {
     pub token: String,
-    pub webhook_port: Option<u16>,
+    pub owner: Option<i64>,
+    pub broadcast_chats: Vec<i64>,
+    pub allowed_chats: Vec<i64>,
+    pub allowed_users: Vec<i64>,
+    pub denied_chats: Vec<i64>,
+    pub denied_users: Vec<i64>,
+    pub leave_denied_groups: bool,
+    pub webhook_listen: Option<ListenAddr>,
+    pub webhook_path: String,
+    pub webhook_secret_token: Option<String>,
+    pub webhook_url: Option<String>,
+    pub webhook_max_connections: u8,
+    pub webhook_self_signed_cert: Option<PathBuf>,
+    pub webhook_cert: Option<PathBuf>,
+    pub webhook_key: Option<PathBuf>,
+    pub metrics_port: Option<u16>,
     pub proxy: Option<Proxy>,
+    pub api_url: Option<String>,
+    pub api_retry_budget: Duration,
+    pub send_rates: SendRates,
+    pub rate_limits: RateLimits,
+    pub session_lifetimes: SessionLifetimes,
+    pub gc_period: Duration,
+    pub storage: Backend,
+    pub state_file: Option<PathBuf>,
+    pub save_period: Duration,
+    pub shutdown_timeout: Duration,
+    pub ocr_data_path: Option<String>,
+    pub ocr_languages: Vec<Language>,
+    pub disabled_features: Vec<Feature>,
+    pub default_locale: Locale,
+    pub log_level: LevelFilter,
+    pub log_format: LogFormat,
 }
 
+impl Config {
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        !self.disabled_features.contains(&feature)
+    }
+}
+
+const ENV_VAR_PREFIX: &str = "EAIMTY_BOT_";
+
 pub struct ConfigBuilder<'a> {
     opts: Options,
     program: Option<&'a str>,
@@ -18,27 +75,354 @@ impl<'a> ConfigBuilder<'a> {
     pub fn new() -> Self {
         let mut opts = Options::new();
 
-        opts.reqopt(
+        add_opt(
+            &mut opts,
+            "c",
+            "config",
+            "Load options from a TOML config file (command line options and environment variables take precedence)",
+            "CONFIG",
+        );
+
+        add_opt(
+            &mut opts,
             "t",
             "token",
             "Set the Telegram Bot HTTP API token (required)",
             "TOKEN",
         );
 
-        opts.optopt(
+        add_opt(
+            &mut opts,
+            "",
+            "owner",
+            "Set the user ID allowed to use /admin commands",
+            "OWNER",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "broadcast-chats",
+            "Set the chat IDs that /admin broadcast sends to, separated by commas",
+            "BROADCAST_CHATS",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "allow-chats",
+            "Only handle updates from these chat IDs or allowed users, separated by commas",
+            "CHATS",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "allow-users",
+            "Only handle updates from these user IDs or allowed chats, separated by commas",
+            "USERS",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "deny-chats",
+            "Ignore updates from these chat IDs, separated by commas",
+            "CHATS",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "deny-users",
+            "Ignore updates from these user IDs, separated by commas",
+            "USERS",
+        );
+
+        add_flag(
+            &mut opts,
+            "leave-denied-groups",
+            "Leave groups that are not allowed",
+        );
+
+        add_opt(
+            &mut opts,
             "w",
             "webhook-port",
-            "Run in webhook mode listening port (1 ~ 65535)",
+            "Run in webhook mode listening on this port on all IPv4 interfaces (1 ~ 65535)",
             "WEBHOOK_PORT",
         );
 
-        opts.optopt(
+        add_opt(
+            &mut opts,
+            "",
+            "webhook-listen",
+            "Run in webhook mode listening on this address instead of --webhook-port (supported: IP:PORT, unix:PATH)",
+            "WEBHOOK_LISTEN",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "webhook-path",
+            "Accept webhook requests only at this path (default: the path of --webhook-url, or /)",
+            "WEBHOOK_PATH",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "webhook-secret-token",
+            "Accept webhook requests only with this X-Telegram-Bot-Api-Secret-Token header (1 ~ 256 characters of A-Z, a-z, 0-9, _ and -) (required without --webhook-url) (default: random)",
+            "WEBHOOK_SECRET_TOKEN",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "webhook-url",
+            "Register this public HTTPS URL as the webhook on startup (requires --webhook-port or --webhook-listen)",
+            "WEBHOOK_URL",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "webhook-max-connections",
+            "Set the maximum number of simultaneous webhook connections from Telegram (1 ~ 100) (default: 40)",
+            "WEBHOOK_MAX_CONNECTIONS",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "webhook-self-signed-cert",
+            "Upload this self-signed PEM certificate to Telegram when registering the webhook",
+            "WEBHOOK_SELF_SIGNED_CERT",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "webhook-cert",
+            "Serve the webhook over HTTPS with this PEM certificate chain, reloaded when changed",
+            "WEBHOOK_CERT",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "webhook-key",
+            "Private key of the webhook certificate in PEM format",
+            "WEBHOOK_KEY",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "metrics-port",
+            "Serve Prometheus metrics at /metrics and health checks at /healthz on this port (1 ~ 65535)",
+            "METRICS_PORT",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "api-url",
+            "Set the Bot API server URL, e.g. a self-hosted telegram-bot-api server (default: https://api.telegram.org)",
+            "API_URL",
+        );
+
+        add_opt(
+            &mut opts,
             "",
             "proxy",
             "Set proxy  (supported: http, https, socks5)",
             "PROXY",
         );
 
+        add_opt(
+            &mut opts,
+            "",
+            "api-retry-budget",
+            "Set how long to keep retrying failed Telegram API requests in seconds, 0 to disable (default: 30)",
+            "API_RETRY_BUDGET",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "send-rate",
+            "Set the maximum number of messages sent per second in total (default: 30)",
+            "SEND_RATE",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "chat-send-rate",
+            "Set the maximum number of messages sent per second to a private chat (default: 1)",
+            "CHAT_SEND_RATE",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "group-send-rate",
+            "Set the maximum number of messages sent per minute to a group (default: 20)",
+            "GROUP_SEND_RATE",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "user-rate-limit",
+            "Limit the updates handled per user as COUNT/SECS (default: 20/60)",
+            "USER_RATE_LIMIT",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "chat-rate-limit",
+            "Limit the updates handled per chat as COUNT/SECS (default: 60/60)",
+            "CHAT_RATE_LIMIT",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "callback-rate-limit",
+            "Limit the button clicks handled per user as COUNT/SECS (default: 30/10)",
+            "CALLBACK_RATE_LIMIT",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "command-rate-limits",
+            "Limit specific commands per user, separated by commas (e.g. ocr=3/60,minesweeper=5/60)",
+            "COMMAND_RATE_LIMITS",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "session-lifetime",
+            "Set how long game and OCR sessions can stay idle in seconds (default: 3600)",
+            "SESSION_LIFETIME",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "session-lifetimes",
+            "Override the session lifetime of specific games, separated by commas (e.g. reversi=7200,ocr=600)",
+            "SESSION_LIFETIMES",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "gc-period",
+            "Set the period of cleaning expired sessions in seconds (default: 3)",
+            "GC_PERIOD",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "storage",
+            "Set the session storage backend (supported: memory, sqlite:PATH) (default: memory)",
+            "STORAGE",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "state-file",
+            "Save sessions to this file and restore them at startup",
+            "STATE_FILE",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "save-period",
+            "Set the period of saving sessions in seconds (default: 60)",
+            "SAVE_PERIOD",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "shutdown-timeout",
+            "Set how long to wait for in-flight updates on shutdown in seconds (default: 10)",
+            "SHUTDOWN_TIMEOUT",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "ocr-data-path",
+            "Set the Tesseract language data directory",
+            "OCR_DATA_PATH",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "ocr-languages",
+            "Set OCR target languages, separated by commas (default: eng,jpn,chi_sim,chi_tra)",
+            "OCR_LANGUAGES",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "enable",
+            "Enable features, separated by commas",
+            "FEATURES",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "disable",
+            &format!(
+                "Disable features, separated by commas (supported: {})",
+                Feature::iter()
+                    .map(|feature| feature.as_name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            "FEATURES",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "default-language",
+            "Set the reply language for users whose language is not supported (supported: zh-Hans, zh-Hant, en, ja) (default: zh-Hans)",
+            "DEFAULT_LANGUAGE",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "log-level",
+            "Set the log level (supported: off, error, warn, info, debug, trace) (default: info)",
+            "LOG_LEVEL",
+        );
+
+        add_opt(
+            &mut opts,
+            "",
+            "log-format",
+            "Set the log format (supported: text, json) (default: text)",
+            "LOG_FORMAT",
+        );
+
         opts.optflag("v", "version", "Print the version");
         opts.optflag("h", "help", "Print this help menu");
 
@@ -79,35 +463,1104 @@ impl<'a> ConfigBuilder<'a> {
             bail!("{}", self.get_usage());
         }
 
-        let token = unsafe { matches.opt_str("t").unwrap_unchecked() };
-
-        let webhook_port = if let Some(port) = matches.opt_str("w") {
-            let port = port
-                .parse()
-                .map_err(|err| anyhow!("{err}\n\n{}", self.get_usage()))?;
+        let file =
+            if let Some(path) = self.get_opt(&matches, "config", |path| Ok(path.to_owned()))? {
+                ConfigFile::load(&path)?
+            } else {
+                ConfigFile::default()
+            };
 
-            if port == 0 {
+        let token =
+            if let Some(token) = self.get_opt(&matches, "token", |token| Ok(token.to_owned()))? {
+                token
+            } else if let Some(token) = file.token {
+                token
+            } else {
                 bail!(
-                    "Port 0 cannot be used as the webhook port\n\n{}",
+                    "Telegram Bot HTTP API token is required\n\n{}",
                     self.get_usage()
                 );
+            };
+
+        let owner = self
+            .get_opt(&matches, "owner", |owner| Ok(owner.parse()?))?
+            .or(file.admin.owner);
+
+        let broadcast_chats = self
+            .get_opt(&matches, "broadcast-chats", parse_ids)?
+            .unwrap_or(file.admin.broadcast_chats);
+
+        let allowed_chats = self
+            .get_opt(&matches, "allow-chats", parse_ids)?
+            .unwrap_or(file.access.allow_chats);
+
+        let allowed_users = self
+            .get_opt(&matches, "allow-users", parse_ids)?
+            .unwrap_or(file.access.allow_users);
+
+        let denied_chats = self
+            .get_opt(&matches, "deny-chats", parse_ids)?
+            .unwrap_or(file.access.deny_chats);
+
+        let denied_users = self
+            .get_opt(&matches, "deny-users", parse_ids)?
+            .unwrap_or(file.access.deny_users);
+
+        let leave_denied_groups = self
+            .get_flag(&matches, "leave-denied-groups")?
+            .or(file.access.leave_denied_groups)
+            .unwrap_or(false);
+
+        let parse_port = |port: &str| check_port(port.parse()?);
+        let parse_listen = |listen: &str| listen.parse::<ListenAddr>();
+
+        let (webhook_listen, webhook_port) = (
+            self.get_arg(&matches, "webhook-listen", parse_listen)?,
+            self.get_arg(&matches, "webhook-port", parse_port)?,
+        );
+
+        let (webhook_listen, webhook_port) = if webhook_listen.is_some() || webhook_port.is_some() {
+            (webhook_listen, webhook_port)
+        } else {
+            let (webhook_listen, webhook_port) = (
+                self.get_env("webhook-listen", parse_listen)?,
+                self.get_env("webhook-port", parse_port)?,
+            );
+
+            if webhook_listen.is_some() || webhook_port.is_some() {
+                (webhook_listen, webhook_port)
+            } else {
+                let webhook_listen = if let Some(listen) = &file.webhook.listen {
+                    Some(parse_listen(listen).map_err(|err| invalid_key("webhook.listen", err))?)
+                } else {
+                    None
+                };
+
+                let webhook_port = if let Some(port) = file.webhook.port {
+                    Some(check_port(port).map_err(|err| invalid_key("webhook.port", err))?)
+                } else {
+                    None
+                };
+
+                (webhook_listen, webhook_port)
             }
+        };
+
+        let webhook_listen = match (webhook_listen, webhook_port) {
+            (Some(_), Some(_)) => {
+                bail!("The webhook listen address and port cannot be set together")
+            }
+            (Some(listen), None) => Some(listen),
+            (None, Some(port)) => Some(ListenAddr::Tcp(SocketAddr::from(([0, 0, 0, 0], port)))),
+            (None, None) => None,
+        };
+
+        let webhook_url =
+            if let Some(url) = self.get_opt(&matches, "webhook-url", parse_webhook_url)? {
+                Some(url)
+            } else if let Some(url) = file.webhook.url {
+                Some(parse_webhook_url(&url).map_err(|err| invalid_key("webhook.url", err))?)
+            } else {
+                None
+            };
+
+        if webhook_url.is_some() && webhook_listen.is_none() {
+            bail!("The webhook URL requires a webhook port or listen address");
+        }
+
+        let webhook_path =
+            if let Some(path) = self.get_opt(&matches, "webhook-path", check_webhook_path)? {
+                path
+            } else if let Some(path) = file.webhook.path {
+                check_webhook_path(&path).map_err(|err| invalid_key("webhook.path", err))?
+            } else if let Some(url) = &webhook_url {
+                Url::parse(url)?.path().to_owned()
+            } else {
+                String::from("/")
+            };
+
+        let webhook_secret_token = if let Some(token) =
+            self.get_opt(&matches, "webhook-secret-token", check_secret_token)?
+        {
+            Some(token)
+        } else if let Some(token) = file.webhook.secret_token {
+            Some(
+                check_secret_token(&token)
+                    .map_err(|err| invalid_key("webhook.secret_token", err))?,
+            )
+        } else {
+            None
+        };
+
+        if webhook_listen.is_some() && webhook_url.is_none() && webhook_secret_token.is_none() {
+            bail!("The webhook secret token is required when the webhook URL is not set");
+        }
+
+        let webhook_max_connections = if let Some(max) =
+            self.get_opt(&matches, "webhook-max-connections", |max| {
+                check_max_connections(max.parse()?)
+            })? {
+            max
+        } else if let Some(max) = file.webhook.max_connections {
+            check_max_connections(max).map_err(|err| invalid_key("webhook.max_connections", err))?
+        } else {
+            40
+        };
+
+        let webhook_self_signed_cert = self
+            .get_opt(&matches, "webhook-self-signed-cert", |path| {
+                Ok(PathBuf::from(path))
+            })?
+            .or(file.webhook.self_signed_cert);
+
+        let webhook_cert = self
+            .get_opt(&matches, "webhook-cert", |path| Ok(PathBuf::from(path)))?
+            .or(file.webhook.cert);
+
+        let webhook_key = self
+            .get_opt(&matches, "webhook-key", |path| Ok(PathBuf::from(path)))?
+            .or(file.webhook.key);
 
+        if webhook_cert.is_some() != webhook_key.is_some() {
+            bail!("The webhook certificate and private key must be set together");
+        }
+
+        let metrics_port = if let Some(port) =
+            self.get_opt(&matches, "metrics-port", |port| check_port(port.parse()?))?
+        {
             Some(port)
+        } else if let Some(port) = file.metrics.port {
+            Some(check_port(port).map_err(|err| invalid_key("metrics.port", err))?)
         } else {
             None
         };
 
-        let proxy = if let Some(proxy) = matches.opt_str("proxy") {
-            Some(Proxy::all(&proxy).map_err(|err| anyhow!("{err}\n\n{}", self.get_usage()))?)
+        let proxy =
+            if let Some(proxy) = self.get_opt(&matches, "proxy", |proxy| Ok(Proxy::all(proxy)?))? {
+                Some(proxy)
+            } else if let Some(proxy) = file.proxy {
+                Some(Proxy::all(&proxy).map_err(|err| invalid_key("proxy", err))?)
+            } else {
+                None
+            };
+
+        let api_url = if let Some(url) = self.get_opt(&matches, "api-url", parse_api_url)? {
+            Some(url)
+        } else if let Some(url) = file.api_url {
+            Some(parse_api_url(&url).map_err(|err| invalid_key("api_url", err))?)
         } else {
             None
         };
 
+        let api_retry_budget = if let Some(budget) =
+            self.get_opt(&matches, "api-retry-budget", |secs| {
+                Ok(Duration::from_secs(secs.parse()?))
+            })? {
+            budget
+        } else if let Some(secs) = file.api_retry_budget {
+            Duration::from_secs(secs)
+        } else {
+            Duration::from_secs(30)
+        };
+
+        let mut send_rates = SendRates::default();
+
+        if let Some(rate) = file.send_rate.global {
+            send_rates.global =
+                check_rate(rate).map_err(|err| invalid_key("send_rate.global", err))?;
+        }
+
+        if let Some(rate) = file.send_rate.chat {
+            send_rates.chat = check_rate(rate).map_err(|err| invalid_key("send_rate.chat", err))?;
+        }
+
+        if let Some(rate) = file.send_rate.group {
+            send_rates.group =
+                check_rate(rate).map_err(|err| invalid_key("send_rate.group", err))?;
+        }
+
+        if let Some(rate) = self.get_opt(&matches, "send-rate", |rate| check_rate(rate.parse()?))? {
+            send_rates.global = rate;
+        }
+
+        if let Some(rate) =
+            self.get_opt(&matches, "chat-send-rate", |rate| check_rate(rate.parse()?))?
+        {
+            send_rates.chat = rate;
+        }
+
+        if let Some(rate) = self.get_opt(&matches, "group-send-rate", |rate| {
+            check_rate(rate.parse()?)
+        })? {
+            send_rates.group = rate;
+        }
+
+        let mut rate_limits = RateLimits::default();
+
+        if let Some(limit) = file.rate_limit.user {
+            rate_limits.user = limit
+                .parse()
+                .map_err(|err| invalid_key("rate_limit.user", err))?;
+        }
+
+        if let Some(limit) = file.rate_limit.chat {
+            rate_limits.chat = limit
+                .parse()
+                .map_err(|err| invalid_key("rate_limit.chat", err))?;
+        }
+
+        if let Some(limit) = file.rate_limit.callback {
+            rate_limits.callback = limit
+                .parse()
+                .map_err(|err| invalid_key("rate_limit.callback", err))?;
+        }
+
+        for (command, limit) in file.rate_limit.commands {
+            let limit = check_command(&command)
+                .and_then(|()| limit.parse())
+                .map_err(|err| invalid_key(&format!("rate_limit.commands.{command}"), err))?;
+
+            rate_limits.commands.insert(command, limit);
+        }
+
+        if let Some(limit) = self.get_opt(&matches, "user-rate-limit", |limit| limit.parse())? {
+            rate_limits.user = limit;
+        }
+
+        if let Some(limit) = self.get_opt(&matches, "chat-rate-limit", |limit| limit.parse())? {
+            rate_limits.chat = limit;
+        }
+
+        if let Some(limit) = self.get_opt(&matches, "callback-rate-limit", |limit| limit.parse())? {
+            rate_limits.callback = limit;
+        }
+
+        self.get_opt(&matches, "command-rate-limits", |limits| {
+            override_command_rate_limits(&mut rate_limits, limits)
+        })?;
+
+        let session_lifetime = if let Some(lifetime) =
+            self.get_opt(&matches, "session-lifetime", |secs| {
+                check_secs(secs.parse()?)
+            })? {
+            lifetime
+        } else if let Some(secs) = file.session_lifetime {
+            check_secs(secs).map_err(|err| invalid_key("session_lifetime", err))?
+        } else {
+            Duration::from_secs(3600)
+        };
+
+        let mut session_lifetimes = SessionLifetimes::new(session_lifetime);
+
+        for (pool, secs) in file.session_lifetimes {
+            let key = format!("session_lifetimes.{pool}");
+
+            let lifetime = session_lifetimes
+                .get_mut(&pool)
+                .ok_or_else(|| invalid_key(&key, "Unknown session pool"))?;
+
+            *lifetime = check_secs(secs).map_err(|err| invalid_key(&key, err))?;
+        }
+
+        self.get_opt(&matches, "session-lifetimes", |lifetimes| {
+            override_session_lifetimes(&mut session_lifetimes, lifetimes)
+        })?;
+
+        let gc_period = if let Some(period) =
+            self.get_opt(&matches, "gc-period", |secs| check_secs(secs.parse()?))?
+        {
+            period
+        } else if let Some(secs) = file.gc_period {
+            check_secs(secs).map_err(|err| invalid_key("gc_period", err))?
+        } else {
+            Duration::from_secs(3)
+        };
+
+        let storage =
+            if let Some(storage) = self.get_opt(&matches, "storage", |storage| storage.parse())? {
+                storage
+            } else if let Some(storage) = file.storage {
+                Backend::from_str(&storage).map_err(|err| invalid_key("storage", err))?
+            } else {
+                Backend::Memory
+            };
+
+        let state_file = self
+            .get_opt(&matches, "state-file", |path| Ok(PathBuf::from(path)))?
+            .or(file.state_file);
+
+        let save_period = if let Some(period) =
+            self.get_opt(&matches, "save-period", |secs| check_secs(secs.parse()?))?
+        {
+            period
+        } else if let Some(secs) = file.save_period {
+            check_secs(secs).map_err(|err| invalid_key("save_period", err))?
+        } else {
+            Duration::from_secs(60)
+        };
+
+        let shutdown_timeout = if let Some(timeout) =
+            self.get_opt(&matches, "shutdown-timeout", |secs| {
+                check_secs(secs.parse()?)
+            })? {
+            timeout
+        } else if let Some(secs) = file.shutdown_timeout {
+            check_secs(secs).map_err(|err| invalid_key("shutdown_timeout", err))?
+        } else {
+            Duration::from_secs(10)
+        };
+
+        let ocr_data_path = self
+            .get_opt(&matches, "ocr-data-path", |path| Ok(path.to_owned()))?
+            .or(file.ocr.data_path);
+
+        let ocr_languages = if let Some(langs) =
+            self.get_opt(&matches, "ocr-languages", |langs| {
+                parse_languages(langs.split(','))
+            })? {
+            langs
+        } else if let Some(langs) = file.ocr.languages {
+            parse_languages(langs.iter().map(String::as_str))
+                .map_err(|err| invalid_key("ocr.languages", err))?
+        } else {
+            Language::iter().collect()
+        };
+
+        let mut disabled_features = Vec::new();
+
+        for (name, is_enabled) in file.features {
+            let feature = Feature::from_name(&name)
+                .ok_or_else(|| invalid_key(&format!("features.{name}"), "Unknown feature"))?;
+
+            if !is_enabled {
+                disabled_features.push(feature);
+            }
+        }
+
+        if let Some(features) = self.get_opt(&matches, "enable", |features| {
+            parse_features(features.split(','))
+        })? {
+            for feature in features {
+                disabled_features.retain(|disabled| *disabled != feature);
+            }
+        }
+
+        if let Some(features) = self.get_opt(&matches, "disable", |features| {
+            parse_features(features.split(','))
+        })? {
+            for feature in features {
+                if !disabled_features.contains(&feature) {
+                    disabled_features.push(feature);
+                }
+            }
+        }
+
+        let default_locale =
+            if let Some(locale) = self.get_opt(&matches, "default-language", parse_locale)? {
+                locale
+            } else if let Some(locale) = file.default_language {
+                parse_locale(&locale).map_err(|err| invalid_key("default_language", err))?
+            } else {
+                Locale::default()
+            };
+
+        let log_level = if let Some(level) = self.get_opt(&matches, "log-level", parse_log_level)? {
+            level
+        } else if let Some(level) = file.log.level {
+            parse_log_level(&level).map_err(|err| invalid_key("log.level", err))?
+        } else {
+            LevelFilter::INFO
+        };
+
+        let log_format =
+            if let Some(format) = self.get_opt(&matches, "log-format", parse_log_format)? {
+                format
+            } else if let Some(format) = file.log.format {
+                parse_log_format(&format).map_err(|err| invalid_key("log.format", err))?
+            } else {
+                LogFormat::Text
+            };
+
         Ok(Config {
             token,
-            webhook_port,
+            owner,
+            broadcast_chats,
+            allowed_chats,
+            allowed_users,
+            denied_chats,
+            denied_users,
+            leave_denied_groups,
+            webhook_listen,
+            webhook_path,
+            webhook_secret_token,
+            webhook_url,
+            webhook_max_connections,
+            webhook_self_signed_cert,
+            webhook_cert,
+            webhook_key,
+            metrics_port,
             proxy,
+            api_url,
+            api_retry_budget,
+            send_rates,
+            rate_limits,
+            session_lifetimes,
+            gc_period,
+            storage,
+            state_file,
+            save_period,
+            shutdown_timeout,
+            ocr_data_path,
+            ocr_languages,
+            disabled_features,
+            default_locale,
+            log_level,
+            log_format,
+        })
+    }
+
+    fn get_opt<T>(
+        &self,
+        matches: &Matches,
+        name: &str,
+        parse: impl FnOnce(&str) -> Result<T>,
+    ) -> Result<Option<T>> {
+        if matches.opt_present(name) {
+            return self.get_arg(matches, name, parse);
+        }
+
+        self.get_env(name, parse)
+    }
+
+    fn get_arg<T>(
+        &self,
+        matches: &Matches,
+        name: &str,
+        parse: impl FnOnce(&str) -> Result<T>,
+    ) -> Result<Option<T>> {
+        matches
+            .opt_str(name)
+            .map(|value| parse(&value).map_err(|err| anyhow!("{err}\n\n{}", self.get_usage())))
+            .transpose()
+    }
+
+    fn get_env<T>(&self, name: &str, parse: impl FnOnce(&str) -> Result<T>) -> Result<Option<T>> {
+        let var = get_env_var_name(name);
+
+        match env::var(&var) {
+            Ok(value) => parse(&value)
+                .map(Some)
+                .map_err(|err| anyhow!("Invalid environment variable `{var}`: {err}")),
+            Err(VarError::NotPresent) => Ok(None),
+            Err(err) => bail!("Invalid environment variable `{var}`: {err}"),
+        }
+    }
+
+    fn get_flag(&self, matches: &Matches, name: &str) -> Result<Option<bool>> {
+        if matches.opt_present(name) {
+            return Ok(Some(true));
+        }
+
+        self.get_env(name, parse_bool)
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    token: Option<String>,
+    api_url: Option<String>,
+    proxy: Option<String>,
+    api_retry_budget: Option<u64>,
+    session_lifetime: Option<u64>,
+    session_lifetimes: HashMap<String, u64>,
+    gc_period: Option<u64>,
+    storage: Option<String>,
+    state_file: Option<PathBuf>,
+    save_period: Option<u64>,
+    shutdown_timeout: Option<u64>,
+    default_language: Option<String>,
+    admin: AdminConfigFile,
+    access: AccessConfigFile,
+    send_rate: SendRateConfigFile,
+    rate_limit: RateLimitConfigFile,
+    webhook: WebhookConfigFile,
+    metrics: MetricsConfigFile,
+    ocr: OcrConfigFile,
+    features: HashMap<String, bool>,
+    log: LogConfigFile,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct AdminConfigFile {
+    owner: Option<i64>,
+    broadcast_chats: Vec<i64>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct AccessConfigFile {
+    allow_chats: Vec<i64>,
+    allow_users: Vec<i64>,
+    deny_chats: Vec<i64>,
+    deny_users: Vec<i64>,
+    leave_denied_groups: Option<bool>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SendRateConfigFile {
+    global: Option<u32>,
+    chat: Option<u32>,
+    group: Option<u32>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RateLimitConfigFile {
+    user: Option<String>,
+    chat: Option<String>,
+    callback: Option<String>,
+    commands: HashMap<String, String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct WebhookConfigFile {
+    port: Option<u16>,
+    listen: Option<String>,
+    path: Option<String>,
+    secret_token: Option<String>,
+    url: Option<String>,
+    max_connections: Option<u8>,
+    self_signed_cert: Option<PathBuf>,
+    cert: Option<PathBuf>,
+    key: Option<PathBuf>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MetricsConfigFile {
+    port: Option<u16>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OcrConfigFile {
+    data_path: Option<String>,
+    languages: Option<Vec<String>>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LogConfigFile {
+    level: Option<String>,
+    format: Option<String>,
+}
+
+impl ConfigFile {
+    fn load(path: &str) -> Result<Self> {
+        let file = fs::read_to_string(path)
+            .map_err(|err| anyhow!("Failed to read config file {path}: {err}"))?;
+
+        toml::from_str(&file).map_err(|err| anyhow!("Failed to parse config file {path}: {err}"))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Feature {
+    About,
+    Agree,
+    ConnectFour,
+    Dart,
+    Dice,
+    Minesweeper,
+    Ocr,
+    Reversi,
+    Slot,
+    TicTacToe,
+}
+
+impl Feature {
+    pub fn iter() -> impl Iterator<Item = Self> {
+        [
+            Self::About,
+            Self::Agree,
+            Self::ConnectFour,
+            Self::Dart,
+            Self::Dice,
+            Self::Minesweeper,
+            Self::Ocr,
+            Self::Reversi,
+            Self::Slot,
+            Self::TicTacToe,
+        ]
+        .into_iter()
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        Self::iter().find(|feature| feature.as_name() == s)
+    }
+
+    pub fn as_name(&self) -> &'static str {
+        match self {
+            Self::About => "about",
+            Self::Agree => "agree",
+            Self::ConnectFour => "connectfour",
+            Self::Dart => "dart",
+            Self::Dice => "dice",
+            Self::Minesweeper => "minesweeper",
+            Self::Ocr => "ocr",
+            Self::Reversi => "reversi",
+            Self::Slot => "slot",
+            Self::TicTacToe => "tictactoe",
+        }
+    }
+}
+
+impl Display for Feature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.as_name())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+fn check_port(port: u16) -> Result<u16> {
+    if port == 0 {
+        bail!("Port 0 cannot be used");
+    }
+
+    Ok(port)
+}
+
+fn parse_webhook_url(url: &str) -> Result<String> {
+    if Url::parse(url)?.scheme() != "https" {
+        bail!("Webhook URL must use HTTPS");
+    }
+
+    Ok(url.to_owned())
+}
+
+fn check_webhook_path(path: &str) -> Result<String> {
+    if !path.starts_with('/') {
+        bail!("Webhook path must start with /");
+    }
+
+    Ok(path.to_owned())
+}
+
+fn check_secret_token(token: &str) -> Result<String> {
+    if token.is_empty()
+        || token.len() > 256
+        || !token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        bail!("Secret token must be 1 ~ 256 characters of A-Z, a-z, 0-9, _ and -");
+    }
+
+    Ok(token.to_owned())
+}
+
+fn check_max_connections(max: u8) -> Result<u8> {
+    if !(1..=100).contains(&max) {
+        bail!("Maximum connections must be between 1 and 100");
+    }
+
+    Ok(max)
+}
+
+fn check_secs(secs: u64) -> Result<Duration> {
+    if secs == 0 {
+        bail!("Duration must be greater than 0 seconds");
+    }
+
+    Ok(Duration::from_secs(secs))
+}
+
+fn parse_ids(s: &str) -> Result<Vec<i64>> {
+    s.split(',')
+        .map(|id| id.trim().parse().map_err(|_| anyhow!("Invalid ID: {id}")))
+        .collect()
+}
+
+fn parse_api_url(url: &str) -> Result<String> {
+    let parsed = Url::parse(url)?;
+
+    if !matches!(parsed.scheme(), "http" | "https") {
+        bail!("Unsupported API URL scheme: {}", parsed.scheme());
+    }
+
+    Ok(url.trim_end_matches('/').to_owned())
+}
+
+fn check_rate(rate: u32) -> Result<u32> {
+    if rate == 0 {
+        bail!("Rate must be greater than 0");
+    }
+
+    Ok(rate)
+}
+
+fn override_command_rate_limits(limits: &mut RateLimits, s: &str) -> Result<()> {
+    for item in s.split(',') {
+        let (command, limit) = item
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Invalid command rate limit: {item}"))?;
+
+        let command = command.trim();
+        check_command(command)?;
+
+        limits
+            .commands
+            .insert(command.to_owned(), limit.trim().parse()?);
+    }
+
+    Ok(())
+}
+
+fn check_command(command: &str) -> Result<()> {
+    if !Handler::has_command(command) {
+        bail!("Unknown command: {command}");
+    }
+
+    Ok(())
+}
+
+fn override_session_lifetimes(lifetimes: &mut SessionLifetimes, s: &str) -> Result<()> {
+    for item in s.split(',') {
+        let (pool, secs) = item
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Invalid session lifetime: {item}"))?;
+
+        let lifetime = lifetimes
+            .get_mut(pool.trim())
+            .ok_or_else(|| anyhow!("Unknown session pool: {pool}"))?;
+
+        *lifetime = check_secs(secs.trim().parse()?)?;
+    }
+
+    Ok(())
+}
+
+fn parse_languages<'s>(langs: impl Iterator<Item = &'s str>) -> Result<Vec<Language>> {
+    let mut res = Vec::new();
+
+    for lang in langs {
+        let lang = Language::from_tesseract_data_str(lang.trim())
+            .ok_or_else(|| anyhow!("Unsupported OCR language: {lang}"))?;
+
+        if !res.contains(&lang) {
+            res.push(lang);
+        }
+    }
+
+    if res.is_empty() {
+        bail!("At least one OCR language is required");
+    }
+
+    Ok(res)
+}
+
+fn parse_features<'s>(features: impl Iterator<Item = &'s str>) -> Result<Vec<Feature>> {
+    features
+        .map(|feature| {
+            Feature::from_name(feature.trim()).ok_or_else(|| anyhow!("Unknown feature: {feature}"))
         })
+        .collect()
+}
+
+fn parse_bool(value: &str) -> Result<bool> {
+    match value {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => bail!("expected true or false"),
+    }
+}
+
+fn parse_locale(name: &str) -> Result<Locale> {
+    Locale::from_name(name.trim()).ok_or_else(|| anyhow!("Unknown language: {name}"))
+}
+
+fn parse_log_level(level: &str) -> Result<LevelFilter> {
+    level
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("Unknown log level: {level}"))
+}
+
+fn parse_log_format(format: &str) -> Result<LogFormat> {
+    LogFormat::from_name(format.trim()).ok_or_else(|| anyhow!("Unknown log format: {format}"))
+}
+
+fn add_opt(opts: &mut Options, short_name: &str, long_name: &str, desc: &str, hint: &str) {
+    let desc = format!("{desc} [env: {}]", get_env_var_name(long_name));
+    opts.optopt(short_name, long_name, &desc, hint);
+}
+
+fn add_flag(opts: &mut Options, long_name: &str, desc: &str) {
+    let desc = format!("{desc} [env: {}]", get_env_var_name(long_name));
+    opts.optflag("", long_name, &desc);
+}
+
+fn get_env_var_name(name: &str) -> String {
+    format!("{ENV_VAR_PREFIX}{}", name.to_uppercase().replace('-', "_"))
+}
+
+fn invalid_key(key: &str, err: impl Display) -> anyhow::Error {
+    anyhow!("Invalid `{key}` in config file: {err}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::process;
+
+    const TOKEN: &str = "123456:TEST";
+
+    static ENV_LOCK: Mutex<()> = parking_lot::const_mutex(());
+
+    fn parse(extra_args: &[&str]) -> Result<Config> {
+        let args = ["eaimty_bot", "--token", TOKEN]
+            .iter()
+            .chain(extra_args)
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>();
+
+        ConfigBuilder::new().parse(&args)
+    }
+
+    fn parse_with_file(name: &str, content: &str, extra_args: &[&str]) -> Result<Config> {
+        let path = env::temp_dir().join(format!("eaimty_bot_{name}_{}.toml", process::id()));
+        fs::write(&path, content).unwrap();
+
+        let path = path.to_str().unwrap().to_owned();
+        let args = ["--config", &path]
+            .iter()
+            .chain(extra_args)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let res = parse(&args);
+        fs::remove_file(&path).unwrap();
+        res
+    }
+
+    fn get_error(res: Result<Config>) -> String {
+        match res {
+            Ok(_) => panic!("expected the config to be rejected"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn command_rate_limits_reject_unknown_commands() {
+        let config = parse(&["--command-rate-limits", "ocr=3/60, minesweeper=5/60"]).unwrap();
+        assert_eq!(config.rate_limits.commands["minesweeper"].burst, 5);
+
+        let err = get_error(parse(&["--command-rate-limits", "orc=3/60"]));
+        assert!(err.starts_with("Unknown command: orc"));
+
+        let file = "[rate_limit.commands]\norc = \"3/60\"\n";
+        let err = get_error(parse_with_file("unknown_command", file, &[]));
+        assert_eq!(
+            err,
+            "Invalid `rate_limit.commands.orc` in config file: Unknown command: orc"
+        );
+    }
+
+    #[test]
+    fn options_override_in_order() {
+        let _guard = ENV_LOCK.lock();
+        let var = get_env_var_name("save-period");
+
+        assert_eq!(parse(&[]).unwrap().save_period, Duration::from_secs(60));
+
+        let content = "save_period = 120";
+        let config = parse_with_file("save_period_file", content, &[]).unwrap();
+        assert_eq!(config.save_period, Duration::from_secs(120));
+
+        env::set_var(&var, "180");
+
+        let config = parse_with_file("save_period_env", content, &[]).unwrap();
+        assert_eq!(config.save_period, Duration::from_secs(180));
+
+        let args = ["--save-period", "240"];
+        let config = parse_with_file("save_period_arg", content, &args).unwrap();
+        assert_eq!(config.save_period, Duration::from_secs(240));
+
+        env::remove_var(&var);
+    }
+
+    #[test]
+    fn flags_override_in_order() {
+        let _guard = ENV_LOCK.lock();
+        let var = get_env_var_name("leave-denied-groups");
+
+        assert!(!parse(&[]).unwrap().leave_denied_groups);
+
+        let content = "[access]\nleave_denied_groups = true";
+        let config = parse_with_file("leave_file", content, &[]).unwrap();
+        assert!(config.leave_denied_groups);
+
+        env::set_var(&var, "false");
+
+        let config = parse_with_file("leave_env", content, &[]).unwrap();
+        assert!(!config.leave_denied_groups);
+
+        let args = ["--leave-denied-groups"];
+        let config = parse_with_file("leave_arg", content, &args).unwrap();
+        assert!(config.leave_denied_groups);
+
+        env::remove_var(&var);
+    }
+
+    #[test]
+    fn invalid_env_values_are_rejected() {
+        let _guard = ENV_LOCK.lock();
+        let builder = ConfigBuilder::new();
+        let var = get_env_var_name("test-invalid-value");
+
+        env::set_var(&var, "yes");
+
+        let err = builder
+            .get_env("test-invalid-value", parse_bool)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Invalid environment variable `{var}`: expected true or false")
+        );
+
+        let err = builder
+            .get_env("test-invalid-value", |secs| check_secs(secs.parse()?))
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with(&format!("Invalid environment variable `{var}`: ")));
+
+        env::remove_var(&var);
+    }
+
+    #[test]
+    fn invalid_args_are_rejected() {
+        let cases = [
+            (&["--owner", "alice"][..], "invalid digit found in string"),
+            (&["--allow-chats", "1,x"], "Invalid ID: x"),
+            (&["--metrics-port", "0"], "Port 0 cannot be used"),
+            (
+                &["--save-period", "0"],
+                "Duration must be greater than 0 seconds",
+            ),
+            (&["--send-rate", "0"], "Rate must be greater than 0"),
+            (
+                &["--user-rate-limit", "3"],
+                "Invalid rate limit: 3 (expected COUNT/SECS)",
+            ),
+            (
+                &["--webhook-path", "hook"],
+                "Webhook path must start with /",
+            ),
+            (
+                &["--webhook-secret-token", "a b"],
+                "Secret token must be 1 ~ 256 characters",
+            ),
+            (
+                &["--storage", "redis"],
+                "Unsupported storage backend: redis",
+            ),
+            (&["--disable", "chess"], "Unknown feature: chess"),
+            (&["--default-language", "fr"], "Unknown language: fr"),
+            (&["--log-level", "loud"], "Unknown log level: loud"),
+            (&["--log-format", "xml"], "Unknown log format: xml"),
+        ];
+
+        for (args, message) in cases {
+            let err = get_error(parse(args));
+            assert!(err.starts_with(message), "{args:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn invalid_file_values_are_rejected() {
+        let cases = [
+            (
+                "save_period = 0",
+                "save_period",
+                "Duration must be greater than 0 seconds",
+            ),
+            (
+                "[metrics]\nport = 0",
+                "metrics.port",
+                "Port 0 cannot be used",
+            ),
+            (
+                "[send_rate]\nglobal = 0",
+                "send_rate.global",
+                "Rate must be greater than 0",
+            ),
+            (
+                "[rate_limit]\nuser = \"3\"",
+                "rate_limit.user",
+                "Invalid rate limit: 3",
+            ),
+            (
+                "[webhook]\nurl = \"http://example.com\"",
+                "webhook.url",
+                "Webhook URL must use HTTPS",
+            ),
+            (
+                "[webhook]\npath = \"hook\"",
+                "webhook.path",
+                "Webhook path must start with /",
+            ),
+            (
+                "storage = \"redis\"",
+                "storage",
+                "Unsupported storage backend",
+            ),
+            (
+                "[features]\nchess = false",
+                "features.chess",
+                "Unknown feature",
+            ),
+            (
+                "default_language = \"fr\"",
+                "default_language",
+                "Unknown language: fr",
+            ),
+            (
+                "[log]\nlevel = \"loud\"",
+                "log.level",
+                "Unknown log level: loud",
+            ),
+        ];
+
+        for (i, (content, key, message)) in cases.into_iter().enumerate() {
+            let err = get_error(parse_with_file(&format!("invalid_{i}"), content, &[]));
+            let expected = format!("Invalid `{key}` in config file: {message}");
+            assert!(err.starts_with(&expected), "{key}: {err}");
+        }
+
+        let content = "[access]\nleave_denied_groups = \"yes\"";
+        let err = get_error(parse_with_file("invalid_type", content, &[]));
+        assert!(err.starts_with("Failed to parse config file"));
     }
 }
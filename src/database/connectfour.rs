@@ -1,43 +1,25 @@
+use crate::i18n::Locale;
 use gamie::connect_four::ConnectFour;
-use std::{
-    collections::HashMap,
-    time::{Duration, Instant},
-};
-use xxhash_rust::xxh3::Xxh3Builder;
-
-pub struct SessionPool {
-    pub sessions: HashMap<[i64; 2], Session, Xxh3Builder>,
-}
-
-impl SessionPool {
-    #[allow(clippy::new_without_default)]
-    pub fn new() -> Self {
-        Self {
-            sessions: HashMap::with_hasher(Xxh3Builder::new()),
-        }
-    }
-
-    pub fn collect_garbage(&mut self, lifetime: Duration) {
-        self.sessions
-            .retain(|_, Session { create_time, .. }| create_time.elapsed() < lifetime);
-    }
-}
+use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Session {
     pub game: ConnectFour,
     pub player_0: Option<(i64, String)>,
     pub player_1: Option<(i64, String)>,
-    create_time: Instant,
+    pub message: Option<i64>,
+    #[serde(default)]
+    pub locale: Locale,
 }
 
 impl Session {
-    #[allow(clippy::new_without_default)]
-    pub fn new() -> Self {
+    pub fn new(locale: Locale) -> Self {
         Self {
             game: unsafe { ConnectFour::new().unwrap_unchecked() },
             player_0: None,
             player_1: None,
-            create_time: Instant::now(),
+            message: None,
+            locale,
         }
     }
 }
@@ -1,52 +1,36 @@
+use crate::i18n::Locale;
 use gamie::minesweeper::Minesweeper;
-use rand::rngs::OsRng;
-use std::{
-    collections::HashMap,
-    time::{Duration, Instant},
-};
+use rand::{rngs::OsRng, Error as RandError, RngCore};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Instant};
 use xxhash_rust::xxh3::Xxh3Builder;
 
-pub struct SessionPool {
-    pub sessions: HashMap<[i64; 2], Session, Xxh3Builder>,
-}
-
-impl SessionPool {
-    #[allow(clippy::new_without_default)]
-    pub fn new() -> Self {
-        Self {
-            sessions: HashMap::with_hasher(Xxh3Builder::new()),
-        }
-    }
-
-    pub fn collect_garbage(&mut self, lifetime: Duration) {
-        self.sessions
-            .retain(|_, Session { create_time, .. }| create_time.elapsed() < lifetime);
-    }
-}
-
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Session {
-    pub game: Minesweeper<OsRng>,
+    pub game: Minesweeper<SessionRng>,
     pub players: HashMap<i64, Player, Xxh3Builder>,
+    #[serde(with = "super::snapshot::option_instant")]
     pub start_time: Option<Instant>,
     pub trigger: Option<String>,
-    create_time: Instant,
+    pub message: Option<i64>,
+    #[serde(default)]
+    pub locale: Locale,
 }
 
 impl Session {
-    #[allow(clippy::new_without_default)]
-    pub fn new(height: usize, width: usize, mines: usize) -> Self {
+    pub fn new(height: usize, width: usize, mines: usize, locale: Locale) -> Self {
         Self {
-            game: unsafe {
-                Minesweeper::new(height, width, mines, OsRng::default()).unwrap_unchecked()
-            },
+            game: unsafe { Minesweeper::new(height, width, mines, SessionRng).unwrap_unchecked() },
             players: HashMap::with_hasher(Xxh3Builder::new()),
             start_time: None,
             trigger: None,
-            create_time: Instant::now(),
+            message: None,
+            locale,
         }
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
     pub name: String,
     pub step: usize,
@@ -57,3 +41,24 @@ impl Player {
         Self { name, step: 1 }
     }
 }
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct SessionRng;
+
+impl RngCore for SessionRng {
+    fn next_u32(&mut self) -> u32 {
+        OsRng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        OsRng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        OsRng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), RandError> {
+        OsRng.try_fill_bytes(dest)
+    }
+}
@@ -1,50 +1,261 @@
 use self::{
-    connectfour::SessionPool as ConnectFourSessionPool,
-    minesweeper::SessionPool as MinesweeperSessionPool, ocr::SessionPool as OcrSessionPool,
-    reversi::SessionPool as ReversiSessionPool, tictactoe::SessionPool as TicTacToeSessionPool,
+    access::{Rule, Subject},
+    connectfour::Session as ConnectFourSession,
+    minesweeper::Session as MinesweeperSession,
+    ocr::Session as OcrSession,
+    reversi::Session as ReversiSession,
+    settings::ChatSettings,
+    storage::{Backend, Opener, Pool},
+    tictactoe::Session as TicTacToeSession,
 };
+use anyhow::Result;
 use parking_lot::Mutex;
-use std::{future::Future, sync::Arc, time::Duration};
-use tokio::time;
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    time,
+};
+use tracing::error;
 
+pub mod access;
 pub mod connectfour;
 pub mod minesweeper;
 pub mod ocr;
 pub mod reversi;
+pub mod settings;
+pub mod snapshot;
+pub mod storage;
 pub mod tictactoe;
 
 pub struct Database {
-    pub connectfour: Mutex<ConnectFourSessionPool>,
-    pub minesweeper: Mutex<MinesweeperSessionPool>,
-    pub ocr: Mutex<OcrSessionPool>,
-    pub reversi: Mutex<ReversiSessionPool>,
-    pub tictactoe: Mutex<TicTacToeSessionPool>,
+    pub access: Pool<Subject, Rule>,
+    pub connectfour: Pool<[i64; 2], ConnectFourSession>,
+    pub minesweeper: Pool<[i64; 2], MinesweeperSession>,
+    pub ocr: Pool<[i64; 2], OcrSession>,
+    pub ocr_relay: Pool<[i64; 2], i64>,
+    pub reversi: Pool<[i64; 2], ReversiSession>,
+    pub settings: Pool<i64, ChatSettings>,
+    pub tictactoe: Pool<[i64; 2], TicTacToeSession>,
+    last_gc: Mutex<Instant>,
 }
 
 impl Database {
-    pub fn init(lifetime: Duration, gc_period: Duration) -> (Arc<Self>, impl Future<Output = ()>) {
+    pub fn init(
+        backend: &Backend,
+        lifetimes: SessionLifetimes,
+        gc_period: Duration,
+    ) -> Result<(Arc<Self>, impl Future<Output = ()>, ExpiredSessionReceiver)> {
+        let opener = Opener::new(backend)?;
+
         let db = Arc::new(Self {
-            connectfour: Mutex::new(ConnectFourSessionPool::new()),
-            minesweeper: Mutex::new(MinesweeperSessionPool::new()),
-            ocr: Mutex::new(OcrSessionPool::new()),
-            reversi: Mutex::new(ReversiSessionPool::new()),
-            tictactoe: Mutex::new(TicTacToeSessionPool::new()),
+            access: opener.open("access")?,
+            connectfour: opener.open("connectfour")?,
+            minesweeper: opener.open("minesweeper")?,
+            ocr: opener.open("ocr")?,
+            ocr_relay: opener.open("ocr_relay")?,
+            reversi: opener.open("reversi")?,
+            settings: opener.open("settings")?,
+            tictactoe: opener.open("tictactoe")?,
+            last_gc: Mutex::new(Instant::now()),
         });
 
-        (db.clone(), db.collect_garbage(lifetime, gc_period))
+        let (expired_tx, expired_rx) = mpsc::unbounded_channel();
+
+        Ok((
+            db.clone(),
+            db.collect_garbage(lifetimes, gc_period, expired_tx),
+            expired_rx,
+        ))
+    }
+
+    pub fn count_sessions(&self) -> Result<Vec<(&'static str, usize)>> {
+        Ok(vec![
+            ("connectfour", self.connectfour.lock().count()?),
+            ("minesweeper", self.minesweeper.lock().count()?),
+            ("ocr", self.ocr.lock().count()?),
+            ("ocr_relay", self.ocr_relay.lock().count()?),
+            ("reversi", self.reversi.lock().count()?),
+            ("tictactoe", self.tictactoe.lock().count()?),
+        ])
+    }
+
+    pub fn list_sessions(&self, pool: &str) -> Result<Option<Vec<SessionInfo>>> {
+        Ok(Some(match pool {
+            "connectfour" => list(&self.connectfour)?,
+            "minesweeper" => list(&self.minesweeper)?,
+            "ocr" => list(&self.ocr)?,
+            "reversi" => list(&self.reversi)?,
+            "tictactoe" => list(&self.tictactoe)?,
+            _ => return Ok(None),
+        }))
+    }
+
+    pub fn remove_session(&self, key: &[i64; 2]) -> Result<Option<ExpiredSession>> {
+        if let Some(session) = self.connectfour.lock().remove(key)? {
+            return Ok(Some(ExpiredSession::ConnectFour(session)));
+        }
+
+        if let Some(session) = self.minesweeper.lock().remove(key)? {
+            return Ok(Some(ExpiredSession::Minesweeper(session)));
+        }
+
+        if let Some(session) = self.ocr.lock().remove(key)? {
+            if let Some(relay) = &session.relay {
+                self.ocr_relay.lock().remove(relay)?;
+            }
+
+            return Ok(Some(ExpiredSession::Ocr(session)));
+        }
+
+        if let Some(session) = self.reversi.lock().remove(key)? {
+            return Ok(Some(ExpiredSession::Reversi(session)));
+        }
+
+        if let Some(session) = self.tictactoe.lock().remove(key)? {
+            return Ok(Some(ExpiredSession::TicTacToe(session)));
+        }
+
+        Ok(None)
+    }
+
+    pub fn last_gc(&self) -> Instant {
+        *self.last_gc.lock()
     }
 
-    async fn collect_garbage(self: Arc<Self>, lifetime: Duration, gc_period: Duration) {
+    async fn collect_garbage(
+        self: Arc<Self>,
+        lifetimes: SessionLifetimes,
+        gc_period: Duration,
+        expired_tx: UnboundedSender<([i64; 2], ExpiredSession)>,
+    ) {
         let mut interval = time::interval(gc_period);
 
         loop {
             interval.tick().await;
 
-            self.connectfour.lock().collect_garbage(lifetime);
-            self.minesweeper.lock().collect_garbage(lifetime);
-            self.ocr.lock().collect_garbage(lifetime);
-            self.reversi.lock().collect_garbage(lifetime);
-            self.tictactoe.lock().collect_garbage(lifetime);
+            let connectfour = expire(&self.connectfour, lifetimes.connectfour);
+            let minesweeper = expire(&self.minesweeper, lifetimes.minesweeper);
+            let ocr = expire(&self.ocr, lifetimes.ocr);
+            let reversi = expire(&self.reversi, lifetimes.reversi);
+            let tictactoe = expire(&self.tictactoe, lifetimes.tictactoe);
+
+            let ocr_relay = self.ocr_relay.lock();
+
+            for relay in ocr.iter().filter_map(|(_, session)| session.relay.as_ref()) {
+                if let Err(err) = ocr_relay.remove(relay) {
+                    error!(error = %format!("{err:#}"), "Failed to remove OCR relay");
+                }
+            }
+
+            drop(ocr_relay);
+
+            let expired = connectfour
+                .into_iter()
+                .map(|(key, session)| (key, ExpiredSession::ConnectFour(session)))
+                .chain(
+                    minesweeper
+                        .into_iter()
+                        .map(|(key, session)| (key, ExpiredSession::Minesweeper(session))),
+                )
+                .chain(
+                    ocr.into_iter()
+                        .map(|(key, session)| (key, ExpiredSession::Ocr(session))),
+                )
+                .chain(
+                    reversi
+                        .into_iter()
+                        .map(|(key, session)| (key, ExpiredSession::Reversi(session))),
+                )
+                .chain(
+                    tictactoe
+                        .into_iter()
+                        .map(|(key, session)| (key, ExpiredSession::TicTacToe(session))),
+                );
+
+            for expired in expired {
+                let _ = expired_tx.send(expired);
+            }
+
+            *self.last_gc.lock() = Instant::now();
+        }
+    }
+}
+
+fn expire<V>(pool: &Pool<[i64; 2], V>, lifetime: Duration) -> Vec<([i64; 2], V)> {
+    match pool.lock().expire(lifetime) {
+        Ok(expired) => expired,
+        Err(err) => {
+            error!(error = %format!("{err:#}"), "Failed to expire sessions");
+            Vec::new()
+        }
+    }
+}
+
+fn list<V>(pool: &Pool<[i64; 2], V>) -> Result<Vec<SessionInfo>> {
+    Ok(pool
+        .lock()
+        .entries()?
+        .into_iter()
+        .map(|entry| (entry.key, entry.last_active))
+        .collect())
+}
+
+pub type SessionInfo = ([i64; 2], SystemTime);
+
+pub type ExpiredSessionReceiver = UnboundedReceiver<([i64; 2], ExpiredSession)>;
+
+pub enum ExpiredSession {
+    ConnectFour(ConnectFourSession),
+    Minesweeper(MinesweeperSession),
+    Ocr(OcrSession),
+    Reversi(ReversiSession),
+    TicTacToe(TicTacToeSession),
+}
+
+impl ExpiredSession {
+    pub fn get_pool_name(&self) -> &'static str {
+        match self {
+            Self::ConnectFour(_) => "connectfour",
+            Self::Minesweeper(_) => "minesweeper",
+            Self::Ocr(_) => "ocr",
+            Self::Reversi(_) => "reversi",
+            Self::TicTacToe(_) => "tictactoe",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SessionLifetimes {
+    pub connectfour: Duration,
+    pub minesweeper: Duration,
+    pub ocr: Duration,
+    pub reversi: Duration,
+    pub tictactoe: Duration,
+}
+
+impl SessionLifetimes {
+    pub fn new(lifetime: Duration) -> Self {
+        Self {
+            connectfour: lifetime,
+            minesweeper: lifetime,
+            ocr: lifetime,
+            reversi: lifetime,
+            tictactoe: lifetime,
+        }
+    }
+
+    pub fn get_mut(&mut self, pool: &str) -> Option<&mut Duration> {
+        match pool {
+            "connectfour" => Some(&mut self.connectfour),
+            "minesweeper" => Some(&mut self.minesweeper),
+            "ocr" => Some(&mut self.ocr),
+            "reversi" => Some(&mut self.reversi),
+            "tictactoe" => Some(&mut self.tictactoe),
+            _ => None,
         }
     }
 }
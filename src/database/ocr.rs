@@ -1,63 +1,30 @@
-use std::{
-    collections::HashMap,
-    fmt::{Display, Formatter, Result as FmtResult},
-    time::{Duration, Instant},
-};
-use xxhash_rust::xxh3::Xxh3Builder;
-
-pub struct SessionPool {
-    pub sessions: HashMap<[i64; 2], Session, Xxh3Builder>,
-    pub relay: HashMap<[i64; 2], i64, Xxh3Builder>,
-}
-
-impl SessionPool {
-    #[allow(clippy::new_without_default)]
-    pub fn new() -> Self {
-        Self {
-            sessions: HashMap::with_hasher(Xxh3Builder::new()),
-            relay: HashMap::with_hasher(Xxh3Builder::new()),
-        }
-    }
-
-    pub fn collect_garbage(&mut self, lifetime: Duration) {
-        self.sessions.retain(
-            |_,
-             Session {
-                 create_time, relay, ..
-             }| {
-                if create_time.elapsed() < lifetime {
-                    true
-                } else {
-                    if let Some(relay) = relay {
-                        self.relay.remove(relay);
-                    }
-                    false
-                }
-            },
-        );
-    }
-}
+use crate::i18n::Locale;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result as FmtResult};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Session {
     pub user: i64,
     pub lang: Option<Language>,
     pub relay: Option<[i64; 2]>,
-    create_time: Instant,
+    pub message: Option<i64>,
+    #[serde(default)]
+    pub locale: Locale,
 }
 
 impl Session {
-    #[allow(clippy::new_without_default)]
-    pub fn new(user_id: i64) -> Self {
+    pub fn new(user_id: i64, locale: Locale) -> Self {
         Self {
             user: user_id,
             lang: None,
             relay: None,
-            create_time: Instant::now(),
+            message: None,
+            locale,
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Language {
     English,
     Japanese,
@@ -0,0 +1,59 @@
+use crate::{i18n::Locale, Feature};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatSettings {
+    pub disabled_commands: Vec<Feature>,
+    pub disabled_keywords: Vec<Feature>,
+    pub language: Option<Locale>,
+}
+
+impl ChatSettings {
+    pub fn is_enabled(&self, trigger: Trigger, feature: Feature) -> bool {
+        !self.get_disabled(trigger).contains(&feature)
+    }
+
+    pub fn toggle(&mut self, trigger: Trigger, feature: Feature) {
+        let disabled = match trigger {
+            Trigger::Command => &mut self.disabled_commands,
+            Trigger::Keyword => &mut self.disabled_keywords,
+        };
+
+        if let Some(pos) = disabled.iter().position(|disabled| *disabled == feature) {
+            disabled.remove(pos);
+        } else {
+            disabled.push(feature);
+        }
+    }
+
+    fn get_disabled(&self, trigger: Trigger) -> &[Feature] {
+        match trigger {
+            Trigger::Command => &self.disabled_commands,
+            Trigger::Keyword => &self.disabled_keywords,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    Command,
+    Keyword,
+}
+
+impl Trigger {
+    pub fn as_name(&self) -> &'static str {
+        match self {
+            Self::Command => "command",
+            Self::Keyword => "keyword",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "command" => Some(Self::Command),
+            "keyword" => Some(Self::Keyword),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use super::{
+    access::{Rule, Subject},
+    settings::ChatSettings,
+    storage::Entry,
+    ConnectFourSession, Database, MinesweeperSession, OcrSession, ReversiSession, TicTacToeSession,
+};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{task, time};
+use tracing::error;
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    #[serde(default)]
+    access: Vec<Entry<Subject, Rule>>,
+    connectfour: Vec<Entry<[i64; 2], ConnectFourSession>>,
+    minesweeper: Vec<Entry<[i64; 2], MinesweeperSession>>,
+    ocr: Vec<Entry<[i64; 2], OcrSession>>,
+    ocr_relay: Vec<Entry<[i64; 2], i64>>,
+    reversi: Vec<Entry<[i64; 2], ReversiSession>>,
+    #[serde(default)]
+    settings: Vec<Entry<i64, ChatSettings>>,
+    tictactoe: Vec<Entry<[i64; 2], TicTacToeSession>>,
+}
+
+impl Database {
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        let snapshot = Snapshot {
+            access: self.access.lock().entries()?,
+            connectfour: self.connectfour.lock().entries()?,
+            minesweeper: self.minesweeper.lock().entries()?,
+            ocr: self.ocr.lock().entries()?,
+            ocr_relay: self.ocr_relay.lock().entries()?,
+            reversi: self.reversi.lock().entries()?,
+            settings: self.settings.lock().entries()?,
+            tictactoe: self.tictactoe.lock().entries()?,
+        };
+
+        let path = path.to_owned();
+
+        task::spawn_blocking(move || write_snapshot(&path, &snapshot)).await?
+    }
+
+    pub fn restore(&self, path: &Path) -> Result<()> {
+        let snapshot = match fs::read(path) {
+            Ok(snapshot) => snapshot,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => {
+                return Err(anyhow!(
+                    "Failed to restore sessions from {}: {err}",
+                    path.display()
+                ))
+            }
+        };
+
+        let snapshot = serde_json::from_slice::<Snapshot>(&snapshot)
+            .map_err(|err| anyhow!("Failed to restore sessions from {}: {err}", path.display()))?;
+
+        self.access.lock().restore(snapshot.access)?;
+        self.connectfour.lock().restore(snapshot.connectfour)?;
+        self.minesweeper.lock().restore(snapshot.minesweeper)?;
+        self.ocr.lock().restore(snapshot.ocr)?;
+        self.ocr_relay.lock().restore(snapshot.ocr_relay)?;
+        self.reversi.lock().restore(snapshot.reversi)?;
+        self.settings.lock().restore(snapshot.settings)?;
+        self.tictactoe.lock().restore(snapshot.tictactoe)?;
+
+        Ok(())
+    }
+
+    pub async fn save_periodically(self: Arc<Self>, path: PathBuf, save_period: Duration) {
+        let mut interval = time::interval(save_period);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            match self.save(&path).await {
+                Ok(()) => {}
+                Err(err) => {
+                    error!(error = %format!("{err:#}"), "Failed to save sessions");
+                }
+            }
+        }
+    }
+}
+
+fn write_snapshot(path: &Path, snapshot: &Snapshot) -> Result<()> {
+    let snapshot = serde_json::to_vec(snapshot)?;
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+
+    fs::write(&tmp_path, snapshot)
+        .and_then(|()| fs::rename(&tmp_path, path))
+        .map_err(|err| anyhow!("Failed to save sessions to {}: {err}", path.display()))
+}
+
+pub mod instant {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    pub fn serialize<S>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let time = SystemTime::now() - instant.elapsed();
+        let millis = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        millis.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Instant, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let time = UNIX_EPOCH + Duration::from_millis(u64::deserialize(deserializer)?);
+        let elapsed = SystemTime::now().duration_since(time).unwrap_or_default();
+        let now = Instant::now();
+        Ok(now.checked_sub(elapsed).unwrap_or(now))
+    }
+}
+
+pub mod option_instant {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::Instant;
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapper(#[serde(with = "super::instant")] Instant);
+
+    pub fn serialize<S>(instant: &Option<Instant>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        instant.map(Wrapper).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Instant>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(instant)| instant))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::{storage::Backend, SessionLifetimes},
+        i18n::Locale,
+    };
+    use serde_json::Value;
+    use std::{env, process};
+
+    fn init() -> Arc<Database> {
+        let lifetimes = SessionLifetimes::new(Duration::from_secs(60));
+        let (db, _, _) =
+            Database::init(&Backend::Memory, lifetimes, Duration::from_secs(60)).unwrap();
+        db
+    }
+
+    fn get_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("eaimty_bot_{name}_{}.json", process::id()))
+    }
+
+    fn fill(db: &Database) {
+        let mut tictactoe = TicTacToeSession::new(Locale::En);
+        tictactoe.player_0 = Some((1, "Alice".to_owned()));
+        tictactoe.message = Some(11);
+        db.tictactoe.lock().insert([-100, 10], tictactoe).unwrap();
+
+        let settings = ChatSettings {
+            language: Some(Locale::Ja),
+            ..ChatSettings::default()
+        };
+        db.settings.lock().insert(-100, settings).unwrap();
+
+        db.access
+            .lock()
+            .insert(Subject::User(2), Rule::Deny)
+            .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn save_restore_round_trip() {
+        let path = get_path("snapshot_round_trip");
+
+        let db = init();
+        fill(&db);
+        db.save(&path).await.unwrap();
+
+        let db = init();
+        db.restore(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let tictactoe = db.tictactoe.lock().get(&[-100, 10]).unwrap().unwrap();
+        assert_eq!(tictactoe.player_0, Some((1, "Alice".to_owned())));
+        assert_eq!(tictactoe.message, Some(11));
+        assert_eq!(tictactoe.locale, Locale::En);
+
+        let settings = db.settings.lock().get(&-100).unwrap().unwrap();
+        assert_eq!(settings.language, Some(Locale::Ja));
+
+        let rule = db.access.lock().get(&Subject::User(2)).unwrap();
+        assert!(rule == Some(Rule::Deny));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn restore_accepts_file_without_access_and_settings() {
+        let path = get_path("snapshot_old_file");
+
+        let db = init();
+        fill(&db);
+        db.save(&path).await.unwrap();
+
+        let mut snapshot = serde_json::from_slice::<Value>(&fs::read(&path).unwrap()).unwrap();
+        let fields = snapshot.as_object_mut().unwrap();
+        fields.remove("access");
+        fields.remove("settings");
+        fs::write(&path, serde_json::to_vec(&snapshot).unwrap()).unwrap();
+
+        let db = init();
+        db.restore(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(db.access.lock().count().unwrap(), 0);
+        assert_eq!(db.settings.lock().count().unwrap(), 0);
+        assert_eq!(db.tictactoe.lock().count().unwrap(), 1);
+    }
+}
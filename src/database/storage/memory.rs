@@ -0,0 +1,88 @@
+use super::{Entry, Storage};
+use anyhow::Result;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    time::{Duration, SystemTime},
+};
+use xxhash_rust::xxh3::Xxh3Builder;
+
+pub struct MemoryStorage<K, V> {
+    entries: Mutex<HashMap<K, (V, SystemTime), Xxh3Builder>>,
+}
+
+impl<K, V> MemoryStorage<K, V> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::with_hasher(Xxh3Builder::new())),
+        }
+    }
+}
+
+impl<K, V> Storage<K, V> for MemoryStorage<K, V>
+where
+    K: Eq + Hash + Clone + Send + Sync,
+    V: Clone + Send + Sync,
+{
+    fn get(&self, key: &K) -> Result<Option<V>> {
+        Ok(self.entries.lock().get(key).map(|(value, _)| value.clone()))
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<()> {
+        self.entries.lock().insert(key, (value, SystemTime::now()));
+        Ok(())
+    }
+
+    fn remove(&self, key: &K) -> Result<Option<V>> {
+        Ok(self.entries.lock().remove(key).map(|(value, _)| value))
+    }
+
+    fn expire(&self, lifetime: Duration) -> Result<Vec<(K, V)>> {
+        let mut entries = self.entries.lock();
+
+        let expired = entries
+            .iter()
+            .filter(|(_, (_, last_active))| last_active.elapsed().unwrap_or_default() >= lifetime)
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+
+        Ok(expired
+            .into_iter()
+            .filter_map(|key| {
+                let (key, (value, _)) = entries.remove_entry(&key)?;
+                Some((key, value))
+            })
+            .collect())
+    }
+
+    fn entries(&self) -> Result<Vec<Entry<K, V>>> {
+        Ok(self
+            .entries
+            .lock()
+            .iter()
+            .map(|(key, (value, last_active))| Entry {
+                key: key.clone(),
+                value: value.clone(),
+                last_active: *last_active,
+            })
+            .collect())
+    }
+
+    fn restore(&self, entries: Vec<Entry<K, V>>) -> Result<()> {
+        self.entries.lock().extend(entries.into_iter().map(
+            |Entry {
+                 key,
+                 value,
+                 last_active,
+             }| (key, (value, last_active)),
+        ));
+
+        Ok(())
+    }
+
+    fn count(&self) -> Result<usize> {
+        Ok(self.entries.lock().len())
+    }
+}
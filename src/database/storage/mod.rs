@@ -0,0 +1,117 @@
+use self::{memory::MemoryStorage, sqlite::SqliteStorage};
+use anyhow::{anyhow, Error, Result};
+use parking_lot::{Mutex, MutexGuard};
+use rusqlite::Connection;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    hash::Hash,
+    ops::Deref,
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+pub mod memory;
+pub mod sqlite;
+
+pub trait Storage<K, V>: Send + Sync {
+    fn get(&self, key: &K) -> Result<Option<V>>;
+    fn insert(&self, key: K, value: V) -> Result<()>;
+    fn remove(&self, key: &K) -> Result<Option<V>>;
+    fn expire(&self, lifetime: Duration) -> Result<Vec<(K, V)>>;
+    fn entries(&self) -> Result<Vec<Entry<K, V>>>;
+    fn restore(&self, entries: Vec<Entry<K, V>>) -> Result<()>;
+    fn count(&self) -> Result<usize>;
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Entry<K, V> {
+    pub key: K,
+    pub value: V,
+    pub last_active: SystemTime,
+}
+
+pub struct Pool<K, V> {
+    storage: Box<dyn Storage<K, V>>,
+    lock: Mutex<()>,
+}
+
+impl<K, V> Pool<K, V> {
+    pub fn lock(&self) -> PoolGuard<'_, K, V> {
+        PoolGuard {
+            storage: self.storage.as_ref(),
+            _lock: self.lock.lock(),
+        }
+    }
+}
+
+pub struct PoolGuard<'a, K, V> {
+    storage: &'a dyn Storage<K, V>,
+    _lock: MutexGuard<'a, ()>,
+}
+
+impl<'a, K, V> Deref for PoolGuard<'a, K, V> {
+    type Target = dyn Storage<K, V> + 'a;
+
+    fn deref(&self) -> &Self::Target {
+        self.storage
+    }
+}
+
+pub enum Backend {
+    Memory,
+    Sqlite(PathBuf),
+}
+
+impl FromStr for Backend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "memory" {
+            Ok(Self::Memory)
+        } else if let Some(path) = s.strip_prefix("sqlite:").filter(|path| !path.is_empty()) {
+            Ok(Self::Sqlite(PathBuf::from(path)))
+        } else {
+            Err(anyhow!(
+                "Unsupported storage backend: {s} (supported: memory, sqlite:PATH)"
+            ))
+        }
+    }
+}
+
+pub enum Opener {
+    Memory,
+    Sqlite(Arc<Mutex<Connection>>),
+}
+
+impl Opener {
+    pub fn new(backend: &Backend) -> Result<Self> {
+        match backend {
+            Backend::Memory => Ok(Self::Memory),
+            Backend::Sqlite(path) => {
+                let conn = Connection::open(path).map_err(|err| {
+                    anyhow!("Failed to open SQLite database {}: {err}", path.display())
+                })?;
+
+                Ok(Self::Sqlite(Arc::new(Mutex::new(conn))))
+            }
+        }
+    }
+
+    pub fn open<K, V>(&self, table: &str) -> Result<Pool<K, V>>
+    where
+        K: Serialize + DeserializeOwned + Eq + Hash + Clone + Send + Sync + 'static,
+        V: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+    {
+        let storage: Box<dyn Storage<K, V>> = match self {
+            Self::Memory => Box::new(MemoryStorage::new()),
+            Self::Sqlite(conn) => Box::new(SqliteStorage::new(conn.clone(), table)?),
+        };
+
+        Ok(Pool {
+            storage,
+            lock: Mutex::new(()),
+        })
+    }
+}
@@ -0,0 +1,320 @@
+use super::{Entry, Storage};
+use anyhow::Result;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::task;
+
+pub struct SqliteStorage<K, V> {
+    conn: Arc<Mutex<Connection>>,
+    table: String,
+    _marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> SqliteStorage<K, V> {
+    pub fn new(conn: Arc<Mutex<Connection>>, table: &str) -> Result<Self> {
+        conn.lock().execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {table} (
+                    key TEXT PRIMARY KEY,
+                    value TEXT NOT NULL,
+                    last_active INTEGER NOT NULL
+                )"
+            ),
+            [],
+        )?;
+
+        Ok(Self {
+            conn,
+            table: table.to_owned(),
+            _marker: PhantomData,
+        })
+    }
+
+    fn with_conn<T>(&self, f: impl FnOnce(&mut Connection) -> Result<T>) -> Result<T> {
+        task::block_in_place(|| f(&mut self.conn.lock()))
+    }
+}
+
+impl<K, V> Storage<K, V> for SqliteStorage<K, V>
+where
+    K: Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    fn get(&self, key: &K) -> Result<Option<V>> {
+        let key = serde_json::to_string(key)?;
+
+        let value = self.with_conn(|conn| {
+            Ok(conn
+                .query_row(
+                    &format!("SELECT value FROM {} WHERE key = ?1", self.table),
+                    [&key],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?)
+        })?;
+
+        Ok(value
+            .map(|value| serde_json::from_str(&value))
+            .transpose()?)
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<()> {
+        let key = serde_json::to_string(&key)?;
+        let value = serde_json::to_string(&value)?;
+
+        self.with_conn(|conn| {
+            conn.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {} (key, value, last_active) VALUES (?1, ?2, ?3)",
+                    self.table
+                ),
+                params![key, value, to_millis(SystemTime::now())],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    fn remove(&self, key: &K) -> Result<Option<V>> {
+        let key = serde_json::to_string(key)?;
+
+        let value = self.with_conn(|conn| {
+            let value = conn
+                .query_row(
+                    &format!("SELECT value FROM {} WHERE key = ?1", self.table),
+                    [&key],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?;
+
+            conn.execute(
+                &format!("DELETE FROM {} WHERE key = ?1", self.table),
+                [&key],
+            )?;
+
+            Ok(value)
+        })?;
+
+        Ok(value
+            .map(|value| serde_json::from_str(&value))
+            .transpose()?)
+    }
+
+    fn expire(&self, lifetime: Duration) -> Result<Vec<(K, V)>> {
+        let deadline = to_millis(SystemTime::now() - lifetime);
+
+        let expired = self.with_conn(|conn| {
+            let expired = conn
+                .prepare(&format!(
+                    "SELECT key, value FROM {} WHERE last_active <= ?1",
+                    self.table
+                ))?
+                .query_map([deadline], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            conn.execute(
+                &format!("DELETE FROM {} WHERE last_active <= ?1", self.table),
+                [deadline],
+            )?;
+
+            Ok(expired)
+        })?;
+
+        expired
+            .into_iter()
+            .map(|(key, value)| Ok((serde_json::from_str(&key)?, serde_json::from_str(&value)?)))
+            .collect()
+    }
+
+    fn entries(&self) -> Result<Vec<Entry<K, V>>> {
+        let entries = self.with_conn(|conn| {
+            Ok(conn
+                .prepare(&format!(
+                    "SELECT key, value, last_active FROM {}",
+                    self.table
+                ))?
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, i64>(2)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?)
+        })?;
+
+        entries
+            .into_iter()
+            .map(|(key, value, last_active)| {
+                Ok(Entry {
+                    key: serde_json::from_str(&key)?,
+                    value: serde_json::from_str(&value)?,
+                    last_active: from_millis(last_active),
+                })
+            })
+            .collect()
+    }
+
+    fn restore(&self, entries: Vec<Entry<K, V>>) -> Result<()> {
+        let entries = entries
+            .into_iter()
+            .map(|entry| {
+                Ok((
+                    serde_json::to_string(&entry.key)?,
+                    serde_json::to_string(&entry.value)?,
+                    to_millis(entry.last_active),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+
+            for (key, value, last_active) in entries {
+                tx.execute(
+                    &format!(
+                        "INSERT OR REPLACE INTO {} (key, value, last_active) VALUES (?1, ?2, ?3)",
+                        self.table
+                    ),
+                    params![key, value, last_active],
+                )?;
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn count(&self) -> Result<usize> {
+        let count = self.with_conn(|conn| {
+            Ok(
+                conn.query_row(&format!("SELECT COUNT(*) FROM {}", self.table), [], |row| {
+                    row.get::<_, i64>(0)
+                })?,
+            )
+        })?;
+
+        Ok(count as usize)
+    }
+}
+
+fn to_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+fn from_millis(millis: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, path::PathBuf, process};
+
+    fn get_path(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("eaimty_bot_{name}_{}.db", process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn open(path: &PathBuf, table: &str) -> SqliteStorage<[i64; 2], String> {
+        let conn = Connection::open(path).unwrap();
+        SqliteStorage::new(Arc::new(Mutex::new(conn)), table).unwrap()
+    }
+
+    #[test]
+    fn insert_get_remove_count() {
+        let path = get_path("sqlite_insert");
+        let storage = open(&path, "sessions");
+
+        storage.insert([1, 2], "a".to_owned()).unwrap();
+        storage.insert([1, 3], "b".to_owned()).unwrap();
+        storage.insert([1, 2], "c".to_owned()).unwrap();
+
+        assert_eq!(storage.count().unwrap(), 2);
+        assert_eq!(storage.get(&[1, 2]).unwrap().as_deref(), Some("c"));
+        assert_eq!(storage.get(&[2, 1]).unwrap(), None);
+
+        assert_eq!(storage.remove(&[1, 3]).unwrap().as_deref(), Some("b"));
+        assert_eq!(storage.remove(&[1, 3]).unwrap(), None);
+        assert_eq!(storage.count().unwrap(), 1);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn expire_removes_inactive_entries() {
+        let path = get_path("sqlite_expire");
+        let storage = open(&path, "sessions");
+
+        storage
+            .restore(vec![Entry {
+                key: [1, 2],
+                value: "old".to_owned(),
+                last_active: SystemTime::now() - Duration::from_secs(120),
+            }])
+            .unwrap();
+        storage.insert([1, 3], "new".to_owned()).unwrap();
+
+        let expired = storage.expire(Duration::from_secs(60)).unwrap();
+
+        assert_eq!(expired, vec![([1, 2], "old".to_owned())]);
+        assert_eq!(storage.get(&[1, 2]).unwrap(), None);
+        assert_eq!(storage.get(&[1, 3]).unwrap().as_deref(), Some("new"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn entries_restore_round_trip() {
+        let path = get_path("sqlite_restore");
+        let storage = open(&path, "sessions");
+        let last_active = UNIX_EPOCH + Duration::from_millis(1_600_000_000_123);
+
+        storage
+            .restore(vec![Entry {
+                key: [1, 2],
+                value: "a".to_owned(),
+                last_active,
+            }])
+            .unwrap();
+
+        let copy = open(&path, "copy");
+        copy.restore(storage.entries().unwrap()).unwrap();
+
+        let entries = copy.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, [1, 2]);
+        assert_eq!(entries[0].value, "a");
+        assert_eq!(entries[0].last_active, last_active);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reopened_file_keeps_entries() {
+        let path = get_path("sqlite_reopen");
+
+        let storage = open(&path, "sessions");
+        storage.insert([1, 2], "a".to_owned()).unwrap();
+        drop(storage);
+
+        let storage = open(&path, "sessions");
+        assert_eq!(storage.count().unwrap(), 1);
+        assert_eq!(storage.get(&[1, 2]).unwrap().as_deref(), Some("a"));
+        assert_eq!(open(&path, "other").count().unwrap(), 0);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
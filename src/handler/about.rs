@@ -1,28 +1,28 @@
-use crate::Handler;
+use super::registry::{CommandInfo, Module};
+use crate::{Feature, Handler};
 use anyhow::Result;
 use tgbot::{methods::SendMessage, types::Command};
 
-pub async fn handle_about_command(handler: &Handler, command: &Command) -> Result<bool> {
-    if command.get_name() == "/about" {
-        let msg = command.get_message();
-        let chat_id = msg.get_chat_id();
-        let msg_id = msg.id;
+pub const MODULE: Module = Module {
+    name: "about",
+    feature: Some(Feature::About),
+    commands: &[CommandInfo {
+        name: "about",
+        ..CommandInfo::DEFAULT
+    }],
+    handle_command: Some(|handler, command| Box::pin(handle_about_command(handler, command))),
+    ..Module::DEFAULT
+};
 
-        let about = r#"
-eaimty_bot
+pub async fn handle_about_command(handler: &Handler, command: &Command) -> Result<()> {
+    let msg = command.get_message();
+    let chat_id = msg.get_chat_id();
+    let msg_id = msg.id;
 
-个人用 Telegram Bot
+    let catalog = handler.get_catalog(chat_id, msg.get_user())?;
 
-获取帮助信息 /help
+    let send_message = SendMessage::new(chat_id, catalog.about).reply_to_message_id(msg_id);
+    handler.outbox.send(chat_id, send_message).await?;
 
-源代码：https://github.com/EAimTY/eaimty_bot
-"#;
-
-        let send_message = SendMessage::new(chat_id, about).reply_to_message_id(msg_id);
-        handler.api.execute(send_message).await?;
-
-        return Ok(true);
-    }
-
-    Ok(false)
+    Ok(())
 }
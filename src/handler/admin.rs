@@ -0,0 +1,278 @@
+use super::registry::{CommandInfo, Module};
+use crate::{
+    access::RuleChange,
+    database::access::{Rule, Subject},
+    i18n::{self, Catalog},
+    Handler,
+};
+use anyhow::Result;
+use futures_util::future;
+use std::{fmt::Write, time::SystemTime};
+use tgbot::{
+    methods::SendMessage,
+    types::{Command, Text, TextEntity},
+};
+use tracing::warn;
+
+pub const MODULE: Module = Module {
+    name: "admin",
+    commands: &[CommandInfo {
+        name: "admin",
+        is_hidden: true,
+        ..CommandInfo::DEFAULT
+    }],
+    handle_command: Some(|handler, command| Box::pin(handle_admin_command(handler, command))),
+    ..Module::DEFAULT
+};
+
+const MAX_LISTED_SESSIONS: usize = 50;
+
+pub async fn handle_admin_command(handler: &Handler, command: &Command) -> Result<()> {
+    let msg = command.get_message();
+
+    if handler.config.owner.is_none() || msg.get_user_id() != handler.config.owner {
+        return Ok(());
+    }
+
+    let chat_id = msg.get_chat_id();
+    let msg_id = msg.id;
+
+    let catalog = handler.get_catalog(chat_id, msg.get_user())?;
+
+    let args = command.get_args();
+
+    let reply = match args.first().map(String::as_str) {
+        Some("stats") => get_stats(handler, catalog)?,
+        Some("sessions") if args.len() == 2 => get_sessions(handler, catalog, &args[1])?,
+        Some("kill") if args.len() == 3 => {
+            if let (Ok(session_chat_id), Ok(session_msg_id)) = (args[1].parse(), args[2].parse()) {
+                kill_session(handler, catalog, session_chat_id, session_msg_id).await?
+            } else {
+                catalog.admin_usage.to_owned()
+            }
+        }
+        Some("broadcast") if args.len() > 1 => match msg.get_text().and_then(get_broadcast_text) {
+            Some(text) => broadcast(handler, catalog, &text).await,
+            None => catalog.admin_usage.to_owned(),
+        },
+        Some("access") => list_access_rules(handler, catalog),
+        Some(action @ ("allow" | "deny" | "unlist")) if args.len() == 3 => {
+            let rule = match action {
+                "allow" => Some(Rule::Allow),
+                "deny" => Some(Rule::Deny),
+                _ => None,
+            };
+
+            match (args[1].as_str(), args[2].parse()) {
+                ("chat", Ok(id)) => set_access_rule(handler, catalog, Subject::Chat(id), rule)?,
+                ("user", Ok(id)) => set_access_rule(handler, catalog, Subject::User(id), rule)?,
+                _ => catalog.admin_usage.to_owned(),
+            }
+        }
+        _ => catalog.admin_usage.to_owned(),
+    };
+
+    let send_message = SendMessage::new(chat_id, reply).reply_to_message_id(msg_id);
+    handler.outbox.send(chat_id, send_message).await?;
+
+    Ok(())
+}
+
+fn get_stats(handler: &Handler, catalog: &Catalog) -> Result<String> {
+    let uptime = handler.started.elapsed().as_secs();
+
+    let mut stats = i18n::format(
+        catalog.admin_uptime,
+        &[
+            ("days", &(uptime / 86400)),
+            ("hours", &(uptime % 86400 / 3600)),
+            ("minutes", &(uptime % 3600 / 60)),
+            ("seconds", &(uptime % 60)),
+        ],
+    );
+
+    let _ = write!(stats, "\n\n{}\n", catalog.admin_sessions);
+
+    for (pool, count) in handler.database.count_sessions()? {
+        let line = i18n::format(catalog.admin_count, &[("name", &pool), ("count", &count)]);
+        let _ = writeln!(stats, "{line}");
+    }
+
+    let _ = write!(stats, "\n{}\n", catalog.admin_updates);
+
+    for (kind, count) in handler.metrics.count_updates() {
+        let line = i18n::format(catalog.admin_count, &[("name", &kind), ("count", &count)]);
+        let _ = writeln!(stats, "{line}");
+    }
+
+    Ok(stats)
+}
+
+fn get_sessions(handler: &Handler, catalog: &Catalog, pool: &str) -> Result<String> {
+    let sessions = match handler.database.list_sessions(pool)? {
+        Some(sessions) => sessions,
+        None => return Ok(i18n::format(catalog.admin_unknown_game, &[("name", &pool)])),
+    };
+
+    if sessions.is_empty() {
+        return Ok(catalog.admin_no_sessions.to_owned());
+    }
+
+    let now = SystemTime::now();
+    let mut list = i18n::format(catalog.admin_session_list, &[("count", &sessions.len())]);
+    list.push('\n');
+
+    for ([chat_id, msg_id], last_active) in sessions.iter().take(MAX_LISTED_SESSIONS) {
+        let idle = now.duration_since(*last_active).unwrap_or_default();
+
+        let session = i18n::format(
+            catalog.admin_session,
+            &[
+                ("chat_id", chat_id),
+                ("msg_id", msg_id),
+                ("idle", &idle.as_secs()),
+            ],
+        );
+
+        let _ = writeln!(list, "{session}");
+    }
+
+    if sessions.len() > MAX_LISTED_SESSIONS {
+        list.push_str("……");
+    }
+
+    Ok(list)
+}
+
+async fn kill_session(
+    handler: &Handler,
+    catalog: &Catalog,
+    chat_id: i64,
+    msg_id: i64,
+) -> Result<String> {
+    let session = match handler.database.remove_session(&[chat_id, msg_id])? {
+        Some(session) => session,
+        None => return Ok(catalog.admin_session_not_found.to_owned()),
+    };
+
+    let pool = session.get_pool_name();
+
+    if let Err(err) = super::handle_expired_session(handler, chat_id, session).await {
+        warn!(error = %format!("{err:#}"), chat_id, msg_id, "Failed to close killed session");
+    }
+
+    Ok(i18n::format(
+        catalog.admin_session_killed,
+        &[("name", &pool)],
+    ))
+}
+
+fn list_access_rules(handler: &Handler, catalog: &Catalog) -> String {
+    let rules = handler.access.list_rules();
+
+    if rules.is_empty() {
+        return catalog.admin_no_rules.to_owned();
+    }
+
+    let mut list = format!("{}\n", catalog.admin_rule_list);
+
+    for (subject, rule, is_fixed) in rules {
+        let _ = writeln!(
+            list,
+            "{rule} {subject}{}",
+            if is_fixed {
+                catalog.admin_rule_fixed
+            } else {
+                ""
+            }
+        );
+    }
+
+    list
+}
+
+fn set_access_rule(
+    handler: &Handler,
+    catalog: &Catalog,
+    subject: Subject,
+    rule: Option<Rule>,
+) -> Result<String> {
+    Ok(match (handler.access.set_rule(subject, rule)?, rule) {
+        (RuleChange::Applied, Some(rule)) => i18n::format(
+            catalog.admin_rule_set,
+            &[("rule", &rule), ("subject", &subject)],
+        ),
+        (RuleChange::Applied, None) => {
+            i18n::format(catalog.admin_rule_removed, &[("subject", &subject)])
+        }
+        (RuleChange::Fixed, _) => {
+            i18n::format(catalog.admin_rule_not_editable, &[("subject", &subject)])
+        }
+        (RuleChange::NotPersistent, _) => catalog.admin_rule_not_persistent.to_owned(),
+    })
+}
+
+fn get_broadcast_text(text: &Text) -> Option<String> {
+    let command_end = text
+        .entities
+        .as_ref()?
+        .iter()
+        .find_map(|entity| match entity {
+            TextEntity::BotCommand(position) => Some(position.offset + position.length),
+            _ => None,
+        })?;
+
+    let args = text
+        .data
+        .encode_utf16()
+        .skip(command_end as usize)
+        .collect::<Vec<_>>();
+    let args = String::from_utf16(&args).ok()?;
+
+    let text = args.trim_start().strip_prefix("broadcast")?.trim();
+
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.to_owned())
+    }
+}
+
+async fn broadcast(handler: &Handler, catalog: &Catalog, text: &str) -> String {
+    let chats = &handler.config.broadcast_chats;
+
+    if chats.is_empty() {
+        return catalog.admin_no_broadcast_chats.to_owned();
+    }
+
+    let results = future::join_all(chats.iter().map(|chat_id| {
+        let send_message = SendMessage::new(*chat_id, text);
+        handler.outbox.send(*chat_id, send_message)
+    }))
+    .await;
+
+    let failed = chats
+        .iter()
+        .zip(results)
+        .filter_map(|(chat_id, res)| {
+            res.err().map(|err| {
+                i18n::format(
+                    catalog.admin_broadcast_error,
+                    &[("chat_id", chat_id), ("error", &err)],
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if failed.is_empty() {
+        i18n::format(catalog.admin_broadcast_sent, &[("count", &chats.len())])
+    } else {
+        i18n::format(
+            catalog.admin_broadcast_failed,
+            &[
+                ("count", &(chats.len() - failed.len())),
+                ("errors", &failed.join("\n")),
+            ],
+        )
+    }
+}
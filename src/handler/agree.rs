@@ -1,29 +1,33 @@
-use crate::Handler;
+use super::registry::Module;
+use crate::{Feature, Handler};
 use anyhow::Result;
 use tgbot::{methods::SendMessage, types::Message};
 
-pub async fn handle_agree_message(handler: &Handler, message: &Message) -> Result<bool> {
-    if let Some(text) = message.get_text() {
-        if text.data.contains("有没有") {
-            let chat_id = message.get_chat_id();
-            let msg_id = message.id;
+pub const MODULE: Module = Module {
+    name: "agree",
+    feature: Some(Feature::Agree),
+    keywords: &["有没有"],
+    handle_keyword: Some(|handler, message| Box::pin(handle_agree_keyword(handler, message))),
+    ..Module::DEFAULT
+};
 
-            let send_message = SendMessage::new(chat_id, "没有").reply_to_message_id(msg_id);
-            handler.api.execute(send_message).await?;
+pub async fn handle_agree_keyword(handler: &Handler, message: &Message) -> Result<()> {
+    let chat_id = message.get_chat_id();
+    let msg_id = message.id;
 
-            let send_message = SendMessage::new(chat_id, "没有").reply_to_message_id(msg_id);
-            handler.api.execute(send_message).await?;
+    let catalog = handler.get_catalog(chat_id, message.get_user())?;
 
-            let send_message = SendMessage::new(chat_id, "没有").reply_to_message_id(msg_id);
-            handler.api.execute(send_message).await?;
+    let send_message = SendMessage::new(chat_id, catalog.agree_no).reply_to_message_id(msg_id);
+    handler.outbox.send(chat_id, send_message).await?;
 
-            let send_message =
-                SendMessage::new(chat_id, "好，没有，通过！").reply_to_message_id(msg_id);
-            handler.api.execute(send_message).await?;
+    let send_message = SendMessage::new(chat_id, catalog.agree_no).reply_to_message_id(msg_id);
+    handler.outbox.send(chat_id, send_message).await?;
 
-            return Ok(true);
-        }
-    }
+    let send_message = SendMessage::new(chat_id, catalog.agree_no).reply_to_message_id(msg_id);
+    handler.outbox.send(chat_id, send_message).await?;
 
-    Ok(false)
+    let send_message = SendMessage::new(chat_id, catalog.agree_pass).reply_to_message_id(msg_id);
+    handler.outbox.send(chat_id, send_message).await?;
+
+    Ok(())
 }
@@ -1,4 +1,9 @@
-use crate::{database::connectfour::Session, Handler};
+use super::registry::{CommandInfo, Module};
+use crate::{
+    database::connectfour::Session,
+    i18n::{self, Catalog},
+    Feature, Handler,
+};
 use anyhow::Result;
 use gamie::connect_four::{ConnectFourError, Player};
 use tgbot::{
@@ -9,36 +14,57 @@ use tgbot::{
     },
 };
 
-pub async fn handle_connectfour_command(handler: &Handler, command: &Command) -> Result<bool> {
-    if command.get_name() == "/connectfour" {
-        let msg = command.get_message();
-        let chat_id = msg.get_chat_id();
-        let msg_id = msg.id;
+pub const MODULE: Module = Module {
+    name: "connectfour",
+    feature: Some(Feature::ConnectFour),
+    commands: &[CommandInfo {
+        name: "connectfour",
+        ..CommandInfo::DEFAULT
+    }],
+    callback_prefix: Some("connectfour"),
+    handle_command: Some(|handler, command| Box::pin(handle_connectfour_command(handler, command))),
+    handle_callback_query: Some(|handler, callback_query| {
+        Box::pin(handle_connectfour_callback_query(handler, callback_query))
+    }),
+    ..Module::DEFAULT
+};
+
+pub async fn handle_connectfour_command(handler: &Handler, command: &Command) -> Result<()> {
+    let msg = command.get_message();
+    let chat_id = msg.get_chat_id();
+    let msg_id = msg.id;
+
+    let locale = handler.get_locale(chat_id, msg.get_user())?;
+    let catalog = locale.get_catalog();
+
+    let pool = handler.database.connectfour.lock();
 
-        let mut pool = handler.database.connectfour.lock();
+    let connectfour = Session::new(locale);
 
-        let connectfour = Session::new();
+    let send_message = SendMessage::new(chat_id, get_game_info(catalog, &connectfour))
+        .reply_markup(get_inline_keyboard(&connectfour))
+        .reply_to_message_id(msg_id);
 
-        let send_message = SendMessage::new(chat_id, get_game_info(&connectfour))
-            .reply_markup(get_inline_keyboard(&connectfour))
-            .reply_to_message_id(msg_id);
+    pool.insert([chat_id, msg_id], connectfour)?;
 
-        pool.sessions.insert([chat_id, msg_id], connectfour);
+    drop(pool);
 
-        drop(pool);
+    let board_msg = handler.outbox.send(chat_id, send_message).await?;
 
-        handler.api.execute(send_message).await?;
+    let pool = handler.database.connectfour.lock();
 
-        return Ok(true);
+    if let Some(mut connectfour) = pool.get(&[chat_id, msg_id])? {
+        connectfour.message = Some(board_msg.id);
+        pool.insert([chat_id, msg_id], connectfour)?;
     }
 
-    Ok(false)
+    Ok(())
 }
 
 pub async fn handle_connectfour_callback_query(
     handler: &Handler,
     callback_query: &CallbackQuery,
-) -> Result<bool> {
+) -> Result<()> {
     if let CallbackQuery {
         id,
         from: user,
@@ -53,10 +79,12 @@ pub async fn handle_connectfour_callback_query(
             let chat_id = msg.get_chat_id();
             let user_id = user.id;
 
+            let catalog = handler.get_catalog(chat_id, Some(user))?;
+
             if col < 7 {
-                let mut pool = handler.database.connectfour.lock();
+                let pool = handler.database.connectfour.lock();
 
-                if let Some(connectfour) = pool.sessions.get_mut(&[chat_id, cmd_msg_id]) {
+                if let Some(mut connectfour) = pool.get(&[chat_id, cmd_msg_id])? {
                     let next_player = connectfour.game.get_next_player();
 
                     let is_right_player = match next_player {
@@ -84,28 +112,32 @@ pub async fn handle_connectfour_callback_query(
                                 let edit_message = EditMessageText::new(
                                     chat_id,
                                     msg_id,
-                                    get_game_info(connectfour),
+                                    get_game_info(connectfour.locale.get_catalog(), &connectfour),
                                 )
-                                .reply_markup(get_inline_keyboard(connectfour));
+                                .reply_markup(get_inline_keyboard(&connectfour));
 
                                 let answer_callback_query = AnswerCallbackQuery::new(id);
 
                                 if connectfour.game.is_ended() {
-                                    pool.sessions.remove(&[chat_id, cmd_msg_id]);
+                                    pool.remove(&[chat_id, cmd_msg_id])?;
+                                } else {
+                                    pool.insert([chat_id, cmd_msg_id], connectfour)?;
                                 }
 
                                 drop(pool);
 
                                 tokio::try_join!(
-                                    handler.api.execute(edit_message),
+                                    handler.outbox.edit(chat_id, msg_id, edit_message),
                                     handler.api.execute(answer_callback_query)
                                 )?;
                             }
                             Err(ConnectFourError::ColumnFilled) => {
+                                pool.insert([chat_id, cmd_msg_id], connectfour)?;
+
                                 drop(pool);
 
                                 let answer_callback_query = AnswerCallbackQuery::new(id)
-                                    .text("无法在此落子")
+                                    .text(catalog.invalid_move)
                                     .show_alert(true);
 
                                 handler.api.execute(answer_callback_query).await?;
@@ -116,7 +148,7 @@ pub async fn handle_connectfour_callback_query(
                         drop(pool);
 
                         let answer_callback_query = AnswerCallbackQuery::new(id)
-                            .text("不是你的回合")
+                            .text(catalog.not_your_turn)
                             .show_alert(true);
 
                         handler.api.execute(answer_callback_query).await?;
@@ -125,18 +157,34 @@ pub async fn handle_connectfour_callback_query(
                     drop(pool);
 
                     let answer_callback_query = AnswerCallbackQuery::new(id)
-                        .text("找不到游戏")
+                        .text(catalog.game_not_found)
                         .show_alert(true);
 
                     handler.api.execute(answer_callback_query).await?;
                 }
             }
-
-            return Ok(true);
         }
     }
 
-    Ok(false)
+    Ok(())
+}
+
+pub async fn handle_connectfour_expired(
+    handler: &Handler,
+    chat_id: i64,
+    connectfour: Session,
+) -> Result<()> {
+    if let Some(msg_id) = connectfour.message {
+        let catalog = connectfour.locale.get_catalog();
+
+        let mut info = get_players_info(catalog, &connectfour);
+        info.push_str(catalog.game_timed_out);
+
+        let edit_message = EditMessageText::new(chat_id, msg_id, info);
+        handler.outbox.edit(chat_id, msg_id, edit_message).await?;
+    }
+
+    Ok(())
 }
 
 fn get_inline_keyboard(connectfour: &Session) -> InlineKeyboardMarkup {
@@ -178,44 +226,48 @@ fn parse_callback_data(data: &str) -> Option<usize> {
     None
 }
 
-fn get_game_info(connectfour: &Session) -> String {
-    let mut info = String::from("四子棋\n\n");
+fn get_players_info(catalog: &Catalog, connectfour: &Session) -> String {
+    let mut info = format!("{}\n\n", catalog.connectfour_title);
 
     if let Some((_, player_0)) = &connectfour.player_0 {
-        info.push_str("🔴：");
-        info.push_str(player_0);
+        let player = i18n::format(catalog.player, &[("piece", &"🔴"), ("name", player_0)]);
+        info.push_str(&player);
         info.push('\n');
     }
 
     if let Some((_, player_1)) = &connectfour.player_1 {
-        info.push_str("🟡：");
-        info.push_str(player_1);
+        let player = i18n::format(catalog.player, &[("piece", &"🟡"), ("name", player_1)]);
+        info.push_str(&player);
         info.push('\n');
     }
 
     info.push('\n');
 
+    info
+}
+
+fn get_game_info(catalog: &Catalog, connectfour: &Session) -> String {
+    let mut info = get_players_info(catalog, connectfour);
+
     if connectfour.game.is_ended() {
         match connectfour.game.get_winner() {
             Some(Player::Player0) => {
                 let (_, player_0) = unsafe { connectfour.player_0.as_ref().unwrap_unchecked() };
-                info.push_str(player_0);
-                info.push_str(" 赢了");
+                info.push_str(&i18n::format(catalog.winner, &[("name", player_0)]));
             }
             Some(Player::Player1) => {
                 let (_, player_1) = unsafe { connectfour.player_1.as_ref().unwrap_unchecked() };
-                info.push_str(player_1);
-                info.push_str(" 赢了");
+                info.push_str(&i18n::format(catalog.winner, &[("name", player_1)]));
             }
-            None => info.push_str("平局"),
+            None => info.push_str(catalog.draw),
         }
     } else {
-        info.push_str("轮到：");
+        let piece = match connectfour.game.get_next_player() {
+            Player::Player0 => "🔴",
+            Player::Player1 => "🟡",
+        };
 
-        match connectfour.game.get_next_player() {
-            Player::Player0 => info.push('🔴'),
-            Player::Player1 => info.push('🟡'),
-        }
+        info.push_str(&i18n::format(catalog.next_player, &[("piece", &piece)]));
     }
 
     info
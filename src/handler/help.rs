@@ -1,31 +1,140 @@
-use crate::Handler;
+use super::registry::{self, CommandInfo, CommandScope, Module, MODULES};
+use crate::{
+    database::settings::{ChatSettings, Trigger},
+    i18n::{self, Catalog, CommandText},
+    Handler,
+};
 use anyhow::Result;
+use std::fmt::Write;
 use tgbot::{methods::SendMessage, types::Command};
 
-pub async fn handle_help_command(handler: &Handler, command: &Command) -> Result<bool> {
-    if command.get_name() == "/help" {
-        let msg = command.get_message();
-        let chat_id = msg.get_chat_id();
-        let msg_id = msg.id;
-
-        let help = r#"
-/about - 关于
-/connectfour - 玩四子棋
-/dart - 掷一枚飞标
-/dice - 掷一枚骰子
-/minesweeper [棋盘高] [棋盘宽] [地雷数] - 玩扫雷
-/ocr - 识别图片中文字
-/reversi - 玩黑白棋
-/slot - 转一次老虎机
-/tictactoe - 玩 Tic-Tac-Toe
-/help - 帮助信息
-"#;
-
-        let send_message = SendMessage::new(chat_id, help).reply_to_message_id(msg_id);
-        handler.api.execute(send_message).await?;
-
-        return Ok(true);
+pub const MODULE: Module = Module {
+    name: "help",
+    commands: &[CommandInfo {
+        name: "help",
+        ..CommandInfo::DEFAULT
+    }],
+    handle_command: Some(|handler, command| Box::pin(handle_help_command(handler, command))),
+    ..Module::DEFAULT
+};
+
+pub async fn handle_help_command(handler: &Handler, command: &Command) -> Result<()> {
+    let msg = command.get_message();
+    let chat_id = msg.get_chat_id();
+    let msg_id = msg.id;
+
+    let settings = handler
+        .database
+        .settings
+        .lock()
+        .get(&chat_id)?
+        .unwrap_or_default();
+
+    let catalog = handler.get_catalog(chat_id, msg.get_user())?;
+    let scope = CommandScope::from_message(msg);
+
+    let help = match command.get_args().first() {
+        Some(name) => get_command_help(handler, catalog, &settings, scope, name)?,
+        None => get_help(handler, catalog, &settings, scope)?,
+    };
+
+    let send_message = SendMessage::new(chat_id, help).reply_to_message_id(msg_id);
+    handler.outbox.send(chat_id, send_message).await?;
+
+    Ok(())
+}
+
+fn get_help(
+    handler: &Handler,
+    catalog: &Catalog,
+    settings: &ChatSettings,
+    scope: CommandScope,
+) -> Result<String> {
+    let mut help = String::new();
+
+    for module in MODULES {
+        if !module.is_enabled_in(&handler.config, settings, Trigger::Command) {
+            continue;
+        }
+
+        let commands = module
+            .commands
+            .iter()
+            .filter(|command| command.is_listed_in(scope));
+
+        for command in commands {
+            let command = catalog.get_command(command.name)?;
+            let _ = writeln!(help, "{} - {}", get_syntax(command), command.description);
+        }
+    }
+
+    help.push('\n');
+    help.push_str(catalog.help_hint);
+
+    Ok(help)
+}
+
+fn get_command_help(
+    handler: &Handler,
+    catalog: &Catalog,
+    settings: &ChatSettings,
+    scope: CommandScope,
+    name: &str,
+) -> Result<String> {
+    let name = name.trim_start_matches('/');
+
+    let found = registry::find_command(name)
+        .filter(|module| module.is_enabled(&handler.config))
+        .filter(|module| {
+            module
+                .commands
+                .iter()
+                .any(|command| command.name == name && command.is_listed_in(scope))
+        });
+
+    let module = match found {
+        Some(module) => module,
+        None => {
+            return Ok(i18n::format(
+                catalog.help_unknown_command,
+                &[("name", &name)],
+            ))
+        }
+    };
+
+    if !module.is_enabled_in(&handler.config, settings, Trigger::Command) {
+        return Ok(i18n::format(
+            catalog.help_disabled_command,
+            &[("name", &name)],
+        ));
+    }
+
+    let command = catalog.get_command(name)?;
+
+    let mut help = format!("{} - {}", get_syntax(command), command.description);
+
+    if !command.usage.is_empty() {
+        let _ = write!(help, "\n\n{}", command.usage);
+    }
+
+    if !module.keywords.is_empty()
+        && module.is_enabled_in(&handler.config, settings, Trigger::Keyword)
+    {
+        let keywords = module.keywords.join(catalog.list_separator);
+        help.push_str("\n\n");
+        help.push_str(&i18n::format(
+            catalog.help_keywords,
+            &[("keywords", &keywords)],
+        ));
     }
 
-    Ok(false)
+    Ok(help)
+}
+
+fn get_syntax(command: &CommandText) -> String {
+    if command.args.is_empty() {
+        format!("/{}", command.name)
+    } else {
+        format!("/{} {}", command.name, command.args)
+    }
 }
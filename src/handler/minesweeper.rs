@@ -1,6 +1,8 @@
+use super::registry::{CommandInfo, Module};
 use crate::{
     database::minesweeper::{Player, Session},
-    Handler,
+    i18n::{self, Catalog},
+    Feature, Handler,
 };
 use anyhow::Result;
 use gamie::minesweeper::{Cell, Status};
@@ -13,68 +15,91 @@ use tgbot::{
     },
 };
 
-pub async fn handle_minesweeper_command(handler: &Handler, command: &Command) -> Result<bool> {
-    if command.get_name() == "/minesweeper" {
-        let msg = command.get_message();
-        let chat_id = msg.get_chat_id();
-        let msg_id = msg.id;
-
-        let args = command
-            .get_args()
-            .iter()
-            .filter(|arg| !arg.contains(handler.username.as_ref()));
-
-        fn get_args<'a>(
-            mut args: impl Iterator<Item = &'a String>,
-        ) -> Option<(usize, usize, usize)> {
-            match (args.next(), args.next(), args.next(), args.next()) {
-                (Some(height), Some(width), Some(mines), None) => {
-                    if let (Ok(height), Ok(width), Ok(mines)) =
-                        (height.parse(), width.parse(), mines.parse())
+pub const MODULE: Module = Module {
+    name: "minesweeper",
+    feature: Some(Feature::Minesweeper),
+    commands: &[CommandInfo {
+        name: "minesweeper",
+        ..CommandInfo::DEFAULT
+    }],
+    callback_prefix: Some("minesweeper"),
+    handle_command: Some(|handler, command| Box::pin(handle_minesweeper_command(handler, command))),
+    handle_callback_query: Some(|handler, callback_query| {
+        Box::pin(handle_minesweeper_callback_query(handler, callback_query))
+    }),
+    ..Module::DEFAULT
+};
+
+pub async fn handle_minesweeper_command(handler: &Handler, command: &Command) -> Result<()> {
+    let msg = command.get_message();
+    let chat_id = msg.get_chat_id();
+    let msg_id = msg.id;
+
+    let locale = handler.get_locale(chat_id, msg.get_user())?;
+    let catalog = locale.get_catalog();
+
+    let args = command
+        .get_args()
+        .iter()
+        .filter(|arg| !arg.contains(handler.username.as_ref()));
+
+    fn get_args<'a>(mut args: impl Iterator<Item = &'a String>) -> Option<(usize, usize, usize)> {
+        match (args.next(), args.next(), args.next(), args.next()) {
+            (Some(height), Some(width), Some(mines), None) => {
+                if let (Ok(height), Ok(width), Ok(mines)) =
+                    (height.parse(), width.parse(), mines.parse())
+                {
+                    if (1..=8).contains(&height)
+                        && (1..=8).contains(&width)
+                        && (height - 1) * (width - 1) >= mines
                     {
-                        if height <= 8 && width <= 8 && (height - 1) * (width - 1) >= mines {
-                            return Some((height, width, mines));
-                        }
+                        return Some((height, width, mines));
                     }
                 }
-                (None, None, None, None) => {
-                    return Some((8, 8, 9));
-                }
-                _ => {}
             }
-
-            None
+            (None, None, None, None) => {
+                return Some((8, 8, 9));
+            }
+            _ => {}
         }
 
-        if let Some((height, width, mines)) = get_args(args) {
-            let mut pool = handler.database.minesweeper.lock();
+        None
+    }
+
+    if let Some((height, width, mines)) = get_args(args) {
+        let pool = handler.database.minesweeper.lock();
 
-            let minesweeper = Session::new(height, width, mines);
+        let minesweeper = Session::new(height, width, mines, locale);
 
-            let send_message = SendMessage::new(chat_id, get_game_info(&minesweeper))
-                .reply_markup(get_inline_keyboard(&minesweeper))
-                .reply_to_message_id(msg_id);
+        let send_message = SendMessage::new(chat_id, get_game_info(catalog, &minesweeper))
+            .reply_markup(get_inline_keyboard(&minesweeper))
+            .reply_to_message_id(msg_id);
 
-            pool.sessions.insert([chat_id, msg_id], minesweeper);
+        pool.insert([chat_id, msg_id], minesweeper)?;
 
-            drop(pool);
+        drop(pool);
 
-            handler.api.execute(send_message).await?;
-        } else {
-            let send_message = SendMessage::new(chat_id, "参数错误").reply_to_message_id(msg_id);
-            handler.api.execute(send_message).await?;
-        }
+        let board_msg = handler.outbox.send(chat_id, send_message).await?;
 
-        return Ok(true);
+        let pool = handler.database.minesweeper.lock();
+
+        if let Some(mut minesweeper) = pool.get(&[chat_id, msg_id])? {
+            minesweeper.message = Some(board_msg.id);
+            pool.insert([chat_id, msg_id], minesweeper)?;
+        }
+    } else {
+        let send_message =
+            SendMessage::new(chat_id, catalog.minesweeper_invalid_args).reply_to_message_id(msg_id);
+        handler.outbox.send(chat_id, send_message).await?;
     }
 
-    Ok(false)
+    Ok(())
 }
 
 pub async fn handle_minesweeper_callback_query(
     handler: &Handler,
     callback_query: &CallbackQuery,
-) -> Result<bool> {
+) -> Result<()> {
     if let CallbackQuery {
         id,
         from: user,
@@ -89,9 +114,11 @@ pub async fn handle_minesweeper_callback_query(
             let chat_id = msg.get_chat_id();
             let user_id = user.id;
 
-            let mut pool = handler.database.minesweeper.lock();
+            let catalog = handler.get_catalog(chat_id, Some(user))?;
+
+            let pool = handler.database.minesweeper.lock();
 
-            if let Some(minesweeper) = pool.sessions.get_mut(&[chat_id, cmd_msg_id]) {
+            if let Some(mut minesweeper) = pool.get(&[chat_id, cmd_msg_id])? {
                 if row < minesweeper.game.get_height() && col < minesweeper.game.get_width() {
                     if let Ok(true) = minesweeper.game.click(row, col, true) {
                         if minesweeper.game.get_step_count() == 1 {
@@ -110,20 +137,25 @@ pub async fn handle_minesweeper_callback_query(
                             minesweeper.trigger = Some(user.get_full_name());
                         }
 
-                        let edit_message =
-                            EditMessageText::new(chat_id, msg_id, get_game_info(minesweeper))
-                                .reply_markup(get_inline_keyboard(minesweeper));
+                        let edit_message = EditMessageText::new(
+                            chat_id,
+                            msg_id,
+                            get_game_info(minesweeper.locale.get_catalog(), &minesweeper),
+                        )
+                        .reply_markup(get_inline_keyboard(&minesweeper));
 
                         let answer_callback_query = AnswerCallbackQuery::new(id);
 
                         if minesweeper.game.is_ended() {
-                            pool.sessions.remove(&[chat_id, cmd_msg_id]);
+                            pool.remove(&[chat_id, cmd_msg_id])?;
+                        } else {
+                            pool.insert([chat_id, cmd_msg_id], minesweeper)?;
                         }
 
                         drop(pool);
 
                         tokio::try_join!(
-                            handler.api.execute(edit_message),
+                            handler.outbox.edit(chat_id, msg_id, edit_message),
                             handler.api.execute(answer_callback_query)
                         )?;
                     } else {
@@ -137,17 +169,33 @@ pub async fn handle_minesweeper_callback_query(
                 drop(pool);
 
                 let answer_callback_query = AnswerCallbackQuery::new(id)
-                    .text("找不到游戏")
+                    .text(catalog.game_not_found)
                     .show_alert(true);
 
                 handler.api.execute(answer_callback_query).await?;
             }
-
-            return Ok(true);
         }
     }
 
-    Ok(false)
+    Ok(())
+}
+
+pub async fn handle_minesweeper_expired(
+    handler: &Handler,
+    chat_id: i64,
+    minesweeper: Session,
+) -> Result<()> {
+    if let Some(msg_id) = minesweeper.message {
+        let catalog = minesweeper.locale.get_catalog();
+
+        let mut info = get_players_info(catalog, &minesweeper);
+        info.push_str(catalog.game_timed_out);
+
+        let edit_message = EditMessageText::new(chat_id, msg_id, info);
+        handler.outbox.edit(chat_id, msg_id, edit_message).await?;
+    }
+
+    Ok(())
 }
 
 fn get_revealed_cell_emoji(cell: &Cell) -> &'static str {
@@ -219,50 +267,56 @@ fn parse_callback_data(data: &str) -> Option<(usize, usize)> {
     None
 }
 
-fn get_game_info(minesweeper: &Session) -> String {
-    let mut info = String::from("扫雷\n\n");
+fn get_players_info(catalog: &Catalog, minesweeper: &Session) -> String {
+    let mut info = format!("{}\n\n", catalog.minesweeper_title);
 
     for Player { name, step } in minesweeper.players.values() {
-        info.push_str(name);
-        info.push('：');
-        info.push_str(&step.to_string());
-        info.push_str(" 次操作\n");
+        let steps = i18n::format(
+            catalog.minesweeper_steps,
+            &[("name", name), ("steps", step)],
+        );
+        info.push_str(&steps);
+        info.push('\n');
     }
 
     info.push('\n');
 
-    fn get_time(start: Instant) -> (u64, u64) {
+    info
+}
+
+fn get_game_info(catalog: &Catalog, minesweeper: &Session) -> String {
+    let mut info = get_players_info(catalog, minesweeper);
+
+    fn get_time(catalog: &Catalog, start: Instant) -> String {
         let now = Instant::now();
         let time = now.duration_since(start).as_secs();
-        (time / 60, time % 60)
+
+        i18n::format(
+            catalog.minesweeper_time,
+            &[("minutes", &(time / 60)), ("seconds", &(time % 60))],
+        )
     }
 
     match minesweeper.game.get_game_status() {
         Status::Win => {
             let start_time = unsafe { minesweeper.start_time.unwrap_unchecked() };
-            let (minute, second) = get_time(start_time);
 
-            info.push_str("用时：");
-            info.push_str(&minute.to_string());
-            info.push_str(" 分 ");
-            info.push_str(&second.to_string());
-            info.push_str(" 秒\n");
+            info.push_str(&get_time(catalog, start_time));
+            info.push('\n');
 
-            info.push_str("扫雷成功");
+            info.push_str(catalog.minesweeper_win);
         }
         Status::Exploded(_) => {
             let start_time = unsafe { minesweeper.start_time.unwrap_unchecked() };
-            let (minute, second) = get_time(start_time);
 
-            info.push_str("用时：");
-            info.push_str(&minute.to_string());
-            info.push_str(" 分 ");
-            info.push_str(&second.to_string());
-            info.push_str(" 秒\n");
+            info.push_str(&get_time(catalog, start_time));
+            info.push('\n');
 
             let trigger = unsafe { minesweeper.trigger.as_ref().unwrap_unchecked() };
-            info.push_str(trigger);
-            info.push_str(" 引爆了地雷");
+            info.push_str(&i18n::format(
+                catalog.minesweeper_exploded,
+                &[("name", trigger)],
+            ));
         }
         Status::InProgress => {}
     }
@@ -1,13 +1,29 @@
-use crate::Database;
+use crate::{
+    access::AccessControl,
+    api::Api,
+    database::{settings::Trigger, ExpiredSession, ExpiredSessionReceiver},
+    i18n::{Catalog, Locale},
+    limiter::{RateLimiter, Verdict},
+    metrics::Metrics,
+    outbox::Outbox,
+    Config, Database,
+};
 use anyhow::Result;
-use futures_util::future::BoxFuture;
-use std::sync::Arc;
+use futures_util::future::{self, BoxFuture};
+use registry::{Module, MODULES};
+use std::{future::Future, sync::Arc, time::Instant};
 use tgbot::{
-    types::{CallbackQuery, Command, Message, MessageKind, Update, UpdateKind},
-    Api, UpdateHandler,
+    methods::{AnswerCallbackQuery, LeaveChat, SendMessage},
+    types::{
+        CallbackQuery, ChatMemberUpdated, Command, Message, MessageKind, Update, UpdateKind, User,
+    },
+    UpdateHandler,
 };
+use tokio::sync::{OwnedRwLockReadGuard, RwLock};
+use tracing::{debug, error, field, info, info_span, Instrument, Span};
 
 mod about;
+mod admin;
 mod agree;
 mod connectfour;
 mod dart;
@@ -15,24 +31,110 @@ mod dice;
 mod help;
 mod minesweeper;
 mod ocr;
+mod registry;
 mod reversi;
+mod settings;
 mod slot;
 mod start;
 mod tictactoe;
 
 #[derive(Clone)]
 pub struct Handler {
+    access: Arc<AccessControl>,
     api: Arc<Api>,
+    config: Arc<Config>,
     database: Arc<Database>,
+    metrics: Arc<Metrics>,
+    outbox: Arc<Outbox>,
+    limiter: Arc<RateLimiter>,
+    started: Instant,
     username: Arc<String>,
+    in_flight: Arc<RwLock<bool>>,
 }
 
 impl Handler {
-    pub fn new(database: Arc<Database>, api: Api, username: String) -> Self {
+    pub fn new(
+        config: Arc<Config>,
+        database: Arc<Database>,
+        api: Arc<Api>,
+        metrics: Arc<Metrics>,
+        outbox: Arc<Outbox>,
+        access: Arc<AccessControl>,
+        username: String,
+    ) -> Self {
         Self {
+            access,
+            limiter: Arc::new(RateLimiter::new(config.rate_limits.clone())),
+            started: Instant::now(),
+            config,
             database,
-            api: Arc::new(api),
+            metrics,
+            api,
+            outbox,
             username: Arc::new(username),
+            in_flight: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    pub fn get_locale(&self, chat_id: i64, user: Option<&User>) -> Result<Locale> {
+        let language = self
+            .database
+            .settings
+            .lock()
+            .get(&chat_id)?
+            .and_then(|settings| settings.language);
+
+        let locale = language
+            .or_else(|| {
+                user.and_then(|user| user.language_code.as_deref())
+                    .and_then(Locale::from_language_code)
+            })
+            .unwrap_or(self.config.default_locale);
+
+        Ok(locale)
+    }
+
+    pub fn get_catalog(&self, chat_id: i64, user: Option<&User>) -> Result<&'static Catalog> {
+        Ok(self.get_locale(chat_id, user)?.get_catalog())
+    }
+
+    pub fn has_command(name: &str) -> bool {
+        registry::find_command(name).is_some()
+    }
+
+    pub fn check_command_texts() -> Result<()> {
+        registry::check_command_texts()
+    }
+
+    pub async fn set_my_commands(&self) -> Result<()> {
+        registry::set_my_commands(self).await
+    }
+
+    pub async fn wait_in_flight(&self) {
+        *self.in_flight.write().await = true;
+    }
+
+    fn enter(&self) -> Option<OwnedRwLockReadGuard<bool>> {
+        self.in_flight
+            .clone()
+            .try_read_owned()
+            .ok()
+            .filter(|is_shutdown| !**is_shutdown)
+    }
+
+    pub async fn handle_expired_sessions(self, mut expired: ExpiredSessionReceiver) {
+        while let Some(([chat_id, msg_id], session)) = expired.recv().await {
+            let in_flight = self.in_flight.clone().read_owned().await;
+
+            if *in_flight {
+                break;
+            }
+
+            let span = info_span!("handle_expired_session", chat_id, msg_id);
+
+            log_result(handle_expired_session(&self, chat_id, session))
+                .instrument(span)
+                .await;
         }
     }
 }
@@ -42,29 +144,161 @@ impl UpdateHandler for Handler {
 
     fn handle(&self, update: Update) -> Self::Future {
         let handler = self.clone();
+        let span = info_span!("update", update_id = update.id);
 
-        Box::pin(async move {
-            match handle_update(handler, update).await {
-                Ok(()) => {}
-                Err(err) => {
-                    eprintln!("{err}");
-                }
+        handler
+            .metrics
+            .updates
+            .with_label_values(&[get_update_kind(&update.kind)])
+            .inc();
+
+        let in_flight = match self.enter() {
+            Some(in_flight) => in_flight,
+            None => {
+                debug!(parent: &span, "Dropping update during shutdown");
+                return Box::pin(future::ready(()));
+            }
+        };
+
+        Box::pin(
+            async move {
+                handle_update(handler, update).await;
+                drop(in_flight);
             }
-        })
+            .instrument(span),
+        )
     }
 }
 
-async fn handle_update(handler: Handler, update: Update) -> Result<()> {
+async fn handle_update(handler: Handler, update: Update) {
     match update.kind {
-        UpdateKind::Message(msg) => handle_message(handler, msg).await?,
-        UpdateKind::CallbackQuery(query) => handle_callback_query(handler, query).await?,
+        UpdateKind::Message(msg) => {
+            let span = info_span!(
+                "handle_message",
+                chat_id = msg.get_chat_id(),
+                user_id = msg.get_user_id(),
+                handler = field::Empty,
+            );
+
+            log_result(handle_message(handler, msg))
+                .instrument(span)
+                .await;
+        }
+        UpdateKind::CallbackQuery(query) => {
+            let span = info_span!(
+                "handle_callback_query",
+                chat_id = query.message.as_ref().map(|msg| msg.get_chat_id()),
+                user_id = query.from.id,
+                handler = field::Empty,
+            );
+
+            log_result(handle_callback_query(handler, query))
+                .instrument(span)
+                .await;
+        }
+        UpdateKind::BotStatus(status) => {
+            let span = info_span!(
+                "handle_bot_status",
+                chat_id = status.chat.get_id(),
+                user_id = status.from.id,
+            );
+
+            log_result(handle_bot_status(handler, status))
+                .instrument(span)
+                .await;
+        }
         _ => {}
     }
+}
+
+fn get_update_kind(kind: &UpdateKind) -> &'static str {
+    match kind {
+        UpdateKind::Message(_) => "message",
+        UpdateKind::EditedMessage(_) => "edited_message",
+        UpdateKind::ChannelPost(_) => "channel_post",
+        UpdateKind::EditedChannelPost(_) => "edited_channel_post",
+        UpdateKind::InlineQuery(_) => "inline_query",
+        UpdateKind::ChosenInlineResult(_) => "chosen_inline_result",
+        UpdateKind::CallbackQuery(_) => "callback_query",
+        UpdateKind::ShippingQuery(_) => "shipping_query",
+        UpdateKind::PreCheckoutQuery(_) => "pre_checkout_query",
+        UpdateKind::Poll(_) => "poll",
+        UpdateKind::PollAnswer(_) => "poll_answer",
+        UpdateKind::BotStatus(_) => "my_chat_member",
+        UpdateKind::UserStatus(_) => "chat_member",
+        UpdateKind::ChatJoinRequest(_) => "chat_join_request",
+        UpdateKind::Unknown(_) => "unknown",
+    }
+}
 
-    Ok(())
+async fn log_result(res: impl Future<Output = Result<()>>) {
+    let start = Instant::now();
+    let res = res.await;
+    let latency_ms = start.elapsed().as_millis() as u64;
+
+    match res {
+        Ok(()) => debug!(latency_ms, "Handled"),
+        Err(err) => error!(latency_ms, error = %format!("{err:#}"), "Failed to handle"),
+    }
+}
+
+async fn route(
+    handler: &Handler,
+    name: &'static str,
+    handled: impl Future<Output = Result<bool>>,
+) -> Result<bool> {
+    let res = handled.await;
+
+    if !matches!(res, Ok(false)) {
+        Span::current().record("handler", name);
+    }
+
+    if res.is_err() {
+        handler
+            .metrics
+            .handler_errors
+            .with_label_values(&[name])
+            .inc();
+    }
+
+    res
+}
+
+async fn handle_expired_session(
+    handler: &Handler,
+    chat_id: i64,
+    session: ExpiredSession,
+) -> Result<()> {
+    match session {
+        ExpiredSession::ConnectFour(session) => {
+            connectfour::handle_connectfour_expired(handler, chat_id, session).await
+        }
+        ExpiredSession::Minesweeper(session) => {
+            minesweeper::handle_minesweeper_expired(handler, chat_id, session).await
+        }
+        ExpiredSession::Ocr(session) => ocr::handle_ocr_expired(handler, chat_id, session).await,
+        ExpiredSession::Reversi(session) => {
+            reversi::handle_reversi_expired(handler, chat_id, session).await
+        }
+        ExpiredSession::TicTacToe(session) => {
+            tictactoe::handle_tictactoe_expired(handler, chat_id, session).await
+        }
+    }
 }
 
 async fn handle_message(handler: Handler, message: Message) -> Result<()> {
+    let chat_id = message.get_chat_id();
+
+    if !handler.access.is_allowed(chat_id, message.get_user_id()) {
+        Span::current().record("handler", "access");
+
+        if !handler.access.is_chat_allowed(chat_id) {
+            leave_denied_group(&handler, chat_id).await?;
+        }
+
+        return Ok(());
+    }
+
     if !matches!(message.kind, MessageKind::Private { .. })
         && !message
             .get_text()
@@ -73,29 +307,57 @@ async fn handle_message(handler: Handler, message: Message) -> Result<()> {
         return Ok(());
     }
 
-    if agree::handle_agree_message(&handler, &message).await?
-        || dart::handle_dart_message(&handler, &message).await?
-        || dice::handle_dice_message(&handler, &message).await?
-        || ocr::handle_ocr_message(&handler, &message).await?
-        || slot::handle_slot_message(&handler, &message).await?
-    {
+    if !check_message_rate_limit(&handler, &message).await? {
         return Ok(());
     }
 
+    let settings = handler
+        .database
+        .settings
+        .lock()
+        .get(&chat_id)?
+        .unwrap_or_default();
+
+    let enabled =
+        |module: &Module, trigger| module.is_enabled_in(&handler.config, &settings, trigger);
+
+    let text = message.get_text().map(|text| text.data.as_str());
+
+    for module in MODULES {
+        if let (Some(handle_keyword), Some(text)) = (module.handle_keyword, text) {
+            if enabled(module, Trigger::Keyword)
+                && module.keywords.iter().any(|keyword| text.contains(keyword))
+            {
+                let handled = async { handle_keyword(&handler, &message).await.map(|()| true) };
+                route(&handler, module.name, handled).await?;
+
+                return Ok(());
+            }
+        }
+
+        if let Some(handle_message) = module.handle_message {
+            if enabled(module, Trigger::Command)
+                && route(&handler, module.name, handle_message(&handler, &message)).await?
+            {
+                return Ok(());
+            }
+        }
+    }
+
     if let Ok(cmd) = Command::try_from(message) {
-        if about::handle_about_command(&handler, &cmd).await?
-            || connectfour::handle_connectfour_command(&handler, &cmd).await?
-            || dart::handle_dart_command(&handler, &cmd).await?
-            || dice::handle_dice_command(&handler, &cmd).await?
-            || help::handle_help_command(&handler, &cmd).await?
-            || minesweeper::handle_minesweeper_command(&handler, &cmd).await?
-            || ocr::handle_ocr_command(&handler, &cmd).await?
-            || reversi::handle_reversi_command(&handler, &cmd).await?
-            || slot::handle_slot_command(&handler, &cmd).await?
-            || start::handle_start_command(&handler, &cmd).await?
-            || tictactoe::handle_tictactoe_command(&handler, &cmd).await?
-        {
-            return Ok(());
+        if let Some(module) = registry::find_command(cmd.get_name()) {
+            if let Some(handle_command) = module.handle_command {
+                if enabled(module, Trigger::Command) {
+                    let handled = async { handle_command(&handler, &cmd).await.map(|()| true) };
+                    route(&handler, module.name, handled).await?;
+
+                    handler
+                        .metrics
+                        .commands
+                        .with_label_values(&[cmd.get_name()])
+                        .inc();
+                }
+            }
         }
     }
 
@@ -103,14 +365,115 @@ async fn handle_message(handler: Handler, message: Message) -> Result<()> {
 }
 
 async fn handle_callback_query(handler: Handler, callback_query: CallbackQuery) -> Result<()> {
-    if connectfour::handle_connectfour_callback_query(&handler, &callback_query).await?
-        || minesweeper::handle_minesweeper_callback_query(&handler, &callback_query).await?
-        || ocr::handle_ocr_callback_query(&handler, &callback_query).await?
-        || reversi::handle_reversi_callback_query(&handler, &callback_query).await?
-        || tictactoe::handle_tictactoe_callback_query(&handler, &callback_query).await?
-    {
+    let chat_id = callback_query.message.as_ref().map(|msg| msg.get_chat_id());
+
+    if let Some(chat_id) = chat_id {
+        if !handler
+            .access
+            .is_allowed(chat_id, Some(callback_query.from.id))
+        {
+            Span::current().record("handler", "access");
+            return Ok(());
+        }
+    }
+
+    if let Verdict::Limited { scope, .. } = handler.limiter.check_callback(callback_query.from.id) {
+        record_rate_limited(&handler, scope);
+
+        let catalog = handler.get_catalog(
+            chat_id.unwrap_or(callback_query.from.id),
+            Some(&callback_query.from),
+        )?;
+
+        let answer_callback_query =
+            AnswerCallbackQuery::new(callback_query.id).text(catalog.rate_limited);
+        handler.api.execute(answer_callback_query).await?;
+
         return Ok(());
     }
 
+    let module = callback_query
+        .data
+        .as_deref()
+        .and_then(registry::find_callback_query);
+
+    if let Some(module) = module {
+        if let Some(handle_callback_query) = module.handle_callback_query {
+            if module.is_enabled(&handler.config) {
+                let handled = async {
+                    handle_callback_query(&handler, &callback_query)
+                        .await
+                        .map(|()| true)
+                };
+                route(&handler, module.name, handled).await?;
+            }
+        }
+    }
+
     Ok(())
 }
+
+async fn handle_bot_status(handler: Handler, status: ChatMemberUpdated) -> Result<()> {
+    let chat_id = status.chat.get_id();
+
+    if status.new_chat_member.is_member() && !handler.access.is_chat_allowed(chat_id) {
+        leave_denied_group(&handler, chat_id).await?;
+    }
+
+    Ok(())
+}
+
+async fn leave_denied_group(handler: &Handler, chat_id: i64) -> Result<()> {
+    if handler.config.leave_denied_groups && chat_id < 0 {
+        handler.api.execute(LeaveChat::new(chat_id)).await?;
+        info!(chat_id, "Left denied group");
+    }
+
+    Ok(())
+}
+
+async fn check_message_rate_limit(handler: &Handler, message: &Message) -> Result<bool> {
+    let user_id = match message.get_user_id() {
+        Some(user_id) => user_id,
+        None => return Ok(true),
+    };
+
+    let chat_id = message.get_chat_id();
+
+    let command = message
+        .get_text()
+        .and_then(|text| text.get_bot_commands())
+        .map(|commands| commands[0].command.clone());
+
+    match handler
+        .limiter
+        .check(user_id, Some(chat_id), command.as_deref())
+    {
+        Verdict::Allowed => Ok(true),
+        Verdict::Limited { scope, notify } => {
+            record_rate_limited(handler, scope);
+
+            if notify {
+                let catalog = handler.get_catalog(chat_id, message.get_user())?;
+
+                let send_message =
+                    SendMessage::new(chat_id, catalog.rate_limited).reply_to_message_id(message.id);
+                handler.outbox.send(chat_id, send_message).await?;
+            }
+
+            Ok(false)
+        }
+    }
+}
+
+fn record_rate_limited(handler: &Handler, scope: &'static str) {
+    Span::current().record("handler", "rate_limit");
+
+    handler
+        .metrics
+        .rate_limited
+        .with_label_values(&[scope])
+        .inc();
+
+    debug!(scope, "Rate limited");
+}
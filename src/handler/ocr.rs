@@ -1,10 +1,10 @@
+use super::registry::{CommandInfo, Module};
 use crate::{
     database::ocr::{Language, Session},
-    Handler,
+    i18n::{self, Catalog},
+    Feature, Handler,
 };
 use anyhow::Result;
-use bytes::BufMut;
-use futures_util::StreamExt;
 use leptess::LepTess;
 use tgbot::{
     methods::{AnswerCallbackQuery, EditMessageText, GetFile, SendMessage},
@@ -14,37 +14,59 @@ use tgbot::{
     },
 };
 
-pub async fn handle_ocr_command(handler: &Handler, command: &Command) -> Result<bool> {
-    if command.get_name() == "/ocr" {
-        let msg = command.get_message();
+pub const MODULE: Module = Module {
+    name: "ocr",
+    feature: Some(Feature::Ocr),
+    commands: &[CommandInfo {
+        name: "ocr",
+        ..CommandInfo::DEFAULT
+    }],
+    callback_prefix: Some("ocr"),
+    handle_command: Some(|handler, command| Box::pin(handle_ocr_command(handler, command))),
+    handle_message: Some(|handler, message| Box::pin(handle_ocr_message(handler, message))),
+    handle_callback_query: Some(|handler, callback_query| {
+        Box::pin(handle_ocr_callback_query(handler, callback_query))
+    }),
+    ..Module::DEFAULT
+};
 
-        if let Some(user_id) = msg.get_user_id() {
-            let chat_id = msg.get_chat_id();
-            let msg_id = msg.id;
+pub async fn handle_ocr_command(handler: &Handler, command: &Command) -> Result<()> {
+    let msg = command.get_message();
 
-            let mut pool = handler.database.ocr.lock();
-            let session = Session::new(user_id);
-            pool.sessions.insert([chat_id, msg_id], session);
+    if let Some(user_id) = msg.get_user_id() {
+        let chat_id = msg.get_chat_id();
+        let msg_id = msg.id;
 
-            let send_message = SendMessage::new(chat_id, "请选择 OCR 目标语言")
-                .reply_markup(get_lang_select_keyboard())
-                .reply_to_message_id(msg_id);
+        let locale = handler.get_locale(chat_id, msg.get_user())?;
+        let catalog = locale.get_catalog();
 
-            drop(pool);
+        let pool = handler.database.ocr.lock();
+        let session = Session::new(user_id, locale);
+        pool.insert([chat_id, msg_id], session)?;
 
-            handler.api.execute(send_message).await?;
-        }
+        let send_message = SendMessage::new(chat_id, catalog.ocr_select_language)
+            .reply_markup(get_lang_select_keyboard(&handler.config.ocr_languages))
+            .reply_to_message_id(msg_id);
+
+        drop(pool);
+
+        let prompt_msg = handler.outbox.send(chat_id, send_message).await?;
+
+        let pool = handler.database.ocr.lock();
 
-        return Ok(true);
+        if let Some(mut session) = pool.get(&[chat_id, msg_id])? {
+            session.message = Some(prompt_msg.id);
+            pool.insert([chat_id, msg_id], session)?;
+        }
     }
 
-    Ok(false)
+    Ok(())
 }
 
 pub async fn handle_ocr_callback_query(
     handler: &Handler,
     callback_query: &CallbackQuery,
-) -> Result<bool> {
+) -> Result<()> {
     if let CallbackQuery {
         id,
         from: user,
@@ -53,47 +75,60 @@ pub async fn handle_ocr_callback_query(
         ..
     } = callback_query
     {
-        if let (Some(data), Some(cmd_msg)) = (parse_callback_data(cb_data), &msg.reply_to) {
+        if let (Some(data), Some(cmd_msg)) = (
+            parse_callback_data(cb_data, &handler.config.ocr_languages),
+            &msg.reply_to,
+        ) {
             let cmd_msg_id = cmd_msg.id;
             let msg_id = msg.id;
             let chat_id = msg.get_chat_id();
             let user_id = user.id;
 
-            let mut pool = handler.database.ocr.lock();
+            let catalog = handler.get_catalog(chat_id, Some(user))?;
+
+            let pool = handler.database.ocr.lock();
 
-            if let Some(session) = pool.sessions.get_mut(&[chat_id, cmd_msg_id]) {
+            if let Some(mut session) = pool.get(&[chat_id, cmd_msg_id])? {
                 if session.user == user_id {
+                    let board_catalog = session.locale.get_catalog();
+
                     let edit_message = if let CallbackData::Select(lang) = data {
                         session.lang = Some(lang);
                         session.relay = Some([chat_id, msg_id]);
-                        pool.relay.insert([chat_id, msg_id], cmd_msg_id);
+                        handler
+                            .database
+                            .ocr_relay
+                            .lock()
+                            .insert([chat_id, msg_id], cmd_msg_id)?;
 
                         EditMessageText::new(
                             chat_id,
                             msg_id,
-                            format!("目标语言：{lang}，请以需要识别的图片回复此条消息（以图片方式发送）"),
+                            i18n::format(board_catalog.ocr_send_picture, &[("lang", &lang)]),
                         )
-                        .reply_markup(get_lang_unselect_keyboard())
+                        .reply_markup(get_lang_unselect_keyboard(board_catalog))
                     } else {
                         session.lang = None;
 
-                        EditMessageText::new(chat_id, msg_id, "请选择 OCR 目标语言")
-                            .reply_markup(get_lang_select_keyboard())
+                        EditMessageText::new(chat_id, msg_id, board_catalog.ocr_select_language)
+                            .reply_markup(get_lang_select_keyboard(&handler.config.ocr_languages))
                     };
 
+                    pool.insert([chat_id, cmd_msg_id], session)?;
+
                     let answer_callback_query = AnswerCallbackQuery::new(id);
 
                     drop(pool);
 
                     tokio::try_join!(
-                        handler.api.execute(edit_message),
+                        handler.outbox.edit(chat_id, msg_id, edit_message),
                         handler.api.execute(answer_callback_query)
                     )?;
                 } else {
                     drop(pool);
 
                     let answer_callback_query = AnswerCallbackQuery::new(id)
-                        .text("不是命令触发者")
+                        .text(catalog.ocr_not_initiator)
                         .show_alert(true);
 
                     handler.api.execute(answer_callback_query).await?;
@@ -102,17 +137,26 @@ pub async fn handle_ocr_callback_query(
                 drop(pool);
 
                 let answer_callback_query = AnswerCallbackQuery::new(id)
-                    .text("找不到会话")
+                    .text(catalog.ocr_session_not_found)
                     .show_alert(true);
 
                 handler.api.execute(answer_callback_query).await?;
             }
-
-            return Ok(true);
         }
     }
 
-    Ok(false)
+    Ok(())
+}
+
+pub async fn handle_ocr_expired(handler: &Handler, chat_id: i64, session: Session) -> Result<()> {
+    if let Some(msg_id) = session.message {
+        let catalog = session.locale.get_catalog();
+
+        let edit_message = EditMessageText::new(chat_id, msg_id, catalog.ocr_timed_out);
+        handler.outbox.edit(chat_id, msg_id, edit_message).await?;
+    }
+
+    Ok(())
 }
 
 pub async fn handle_ocr_message(handler: &Handler, message: &Message) -> Result<bool> {
@@ -125,21 +169,21 @@ pub async fn handle_ocr_message(handler: &Handler, message: &Message) -> Result<
         let chat_id = message.get_chat_id();
         let relay_msg_id = relay_msg.id;
 
-        let mut pool = handler.database.ocr.lock();
+        let pool = handler.database.ocr.lock();
+        let relay = handler.database.ocr_relay.lock();
 
-        if let Some(cmd_msg_id) = pool.relay.get(&[chat_id, relay_msg_id]).copied() {
+        if let Some(cmd_msg_id) = relay.get(&[chat_id, relay_msg_id])? {
             if let Some(Session {
                 user,
                 lang: Some(lang),
                 ..
-            }) = pool.sessions.get(&[chat_id, cmd_msg_id])
+            }) = pool.get(&[chat_id, cmd_msg_id])?
             {
-                if user_id == *user {
-                    let lang = *lang;
-
-                    pool.sessions.remove(&[chat_id, cmd_msg_id]);
-                    pool.relay.remove(&[chat_id, relay_msg_id]);
+                if user_id == user {
+                    pool.remove(&[chat_id, cmd_msg_id])?;
+                    relay.remove(&[chat_id, relay_msg_id])?;
 
+                    drop(relay);
                     drop(pool);
 
                     let PhotoSize { file_id, .. } = unsafe {
@@ -155,27 +199,30 @@ pub async fn handle_ocr_message(handler: &Handler, message: &Message) -> Result<
                         ..
                     } = handler.api.execute(get_file).await?
                     {
-                        let mut stream = handler.api.download_file(path).await?;
-
-                        let mut pic = Vec::new();
+                        let pic = handler.api.download_file(&path).await?;
 
-                        while let Some(chunk) = stream.next().await {
-                            pic.put_slice(&chunk?);
-                        }
+                        let timer = handler.metrics.ocr_duration.start_timer();
 
-                        let mut leptess = LepTess::new(None, lang.as_tesseract_data_str())?;
+                        let mut leptess = LepTess::new(
+                            handler.config.ocr_data_path.as_deref(),
+                            lang.as_tesseract_data_str(),
+                        )?;
                         leptess.set_image_from_mem(&pic)?;
                         let res = leptess.get_utf8_text()?;
 
+                        timer.observe_duration();
+
                         let send_message =
                             SendMessage::new(chat_id, res).reply_to_message_id(msg_id);
 
-                        handler.api.execute(send_message).await?;
+                        handler.outbox.send(chat_id, send_message).await?;
                     } else {
-                        let send_message =
-                            SendMessage::new(chat_id, "图片获取失败").reply_to_message_id(msg_id);
+                        let catalog = handler.get_catalog(chat_id, message.get_user())?;
+
+                        let send_message = SendMessage::new(chat_id, catalog.ocr_download_failed)
+                            .reply_to_message_id(msg_id);
 
-                        handler.api.execute(send_message).await?;
+                        handler.outbox.send(chat_id, send_message).await?;
                     }
 
                     return Ok(true);
@@ -187,8 +234,9 @@ pub async fn handle_ocr_message(handler: &Handler, message: &Message) -> Result<
     Ok(false)
 }
 
-fn get_lang_select_keyboard() -> InlineKeyboardMarkup {
-    let vec = Language::iter()
+fn get_lang_select_keyboard(langs: &[Language]) -> InlineKeyboardMarkup {
+    let vec = langs
+        .iter()
         .map(|lang| {
             vec![InlineKeyboardButton::new(
                 lang.to_string(),
@@ -203,9 +251,9 @@ fn get_lang_select_keyboard() -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::from_vec(vec)
 }
 
-fn get_lang_unselect_keyboard() -> InlineKeyboardMarkup {
+fn get_lang_unselect_keyboard(catalog: &Catalog) -> InlineKeyboardMarkup {
     let vec = vec![vec![InlineKeyboardButton::new(
-        "重新选择",
+        catalog.ocr_reselect,
         InlineKeyboardButtonKind::CallbackData(String::from("ocr-unselect")),
     )]];
 
@@ -217,14 +265,16 @@ enum CallbackData {
     Unselect,
 }
 
-fn parse_callback_data(data: &str) -> Option<CallbackData> {
+fn parse_callback_data(data: &str, langs: &[Language]) -> Option<CallbackData> {
     let mut data = data.split('-');
 
     if let (Some("ocr"), Some(target), None) = (data.next(), data.next(), data.next()) {
         if target == "unselect" {
             return Some(CallbackData::Unselect);
         } else if let Some(lang) = Language::from_tesseract_data_str(target) {
-            return Some(CallbackData::Select(lang));
+            if langs.contains(&lang) {
+                return Some(CallbackData::Select(lang));
+            }
         }
     }
 
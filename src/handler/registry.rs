@@ -0,0 +1,190 @@
+use super::{
+    about, admin, agree, connectfour, dart, dice, help, minesweeper, ocr, reversi, settings, slot,
+    start, tictactoe,
+};
+use crate::{
+    database::settings::{ChatSettings, Trigger},
+    i18n::Locale,
+    Config, Feature, Handler,
+};
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+use tgbot::{
+    methods::SetMyCommands,
+    types::{BotCommand, BotCommandScope, CallbackQuery, Command, Message, MessageKind},
+};
+
+pub type CommandHandler = for<'a> fn(&'a Handler, &'a Command) -> BoxFuture<'a, Result<()>>;
+pub type KeywordHandler = for<'a> fn(&'a Handler, &'a Message) -> BoxFuture<'a, Result<()>>;
+pub type MessageHandler = for<'a> fn(&'a Handler, &'a Message) -> BoxFuture<'a, Result<bool>>;
+pub type CallbackQueryHandler =
+    for<'a> fn(&'a Handler, &'a CallbackQuery) -> BoxFuture<'a, Result<()>>;
+
+const LANGUAGES: [(&str, Locale); 3] = [
+    ("zh", Locale::ZhHans),
+    ("en", Locale::En),
+    ("ja", Locale::Ja),
+];
+
+pub const MODULES: &[Module] = &[
+    about::MODULE,
+    admin::MODULE,
+    agree::MODULE,
+    connectfour::MODULE,
+    dart::MODULE,
+    dice::MODULE,
+    help::MODULE,
+    minesweeper::MODULE,
+    ocr::MODULE,
+    reversi::MODULE,
+    settings::MODULE,
+    slot::MODULE,
+    start::MODULE,
+    tictactoe::MODULE,
+];
+
+pub struct Module {
+    pub name: &'static str,
+    pub feature: Option<Feature>,
+    pub commands: &'static [CommandInfo],
+    pub keywords: &'static [&'static str],
+    pub callback_prefix: Option<&'static str>,
+    pub handle_command: Option<CommandHandler>,
+    pub handle_keyword: Option<KeywordHandler>,
+    pub handle_message: Option<MessageHandler>,
+    pub handle_callback_query: Option<CallbackQueryHandler>,
+}
+
+impl Module {
+    pub const DEFAULT: Self = Self {
+        name: "",
+        feature: None,
+        commands: &[],
+        keywords: &[],
+        callback_prefix: None,
+        handle_command: None,
+        handle_keyword: None,
+        handle_message: None,
+        handle_callback_query: None,
+    };
+
+    pub fn is_enabled(&self, config: &Config) -> bool {
+        self.feature
+            .map_or(true, |feature| config.is_enabled(feature))
+    }
+
+    pub fn is_enabled_in(
+        &self,
+        config: &Config,
+        settings: &ChatSettings,
+        trigger: Trigger,
+    ) -> bool {
+        self.is_enabled(config)
+            && self
+                .feature
+                .map_or(true, |feature| settings.is_enabled(trigger, feature))
+    }
+}
+
+pub struct CommandInfo {
+    pub name: &'static str,
+    pub scope: CommandScope,
+    pub is_hidden: bool,
+}
+
+impl CommandInfo {
+    pub const DEFAULT: Self = Self {
+        name: "",
+        scope: CommandScope::All,
+        is_hidden: false,
+    };
+
+    pub fn is_listed_in(&self, scope: CommandScope) -> bool {
+        !self.is_hidden && (self.scope == CommandScope::All || self.scope == scope)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CommandScope {
+    All,
+    Private,
+    Group,
+}
+
+impl CommandScope {
+    pub fn from_message(message: &Message) -> Self {
+        if matches!(message.kind, MessageKind::Private { .. }) {
+            Self::Private
+        } else {
+            Self::Group
+        }
+    }
+}
+
+pub fn find_command(name: &str) -> Option<&'static Module> {
+    let name = name.trim_start_matches('/');
+
+    MODULES
+        .iter()
+        .find(|module| module.commands.iter().any(|command| command.name == name))
+}
+
+pub fn find_callback_query(data: &str) -> Option<&'static Module> {
+    let prefix = data.split('-').next()?;
+
+    MODULES
+        .iter()
+        .find(|module| module.callback_prefix == Some(prefix))
+}
+
+pub fn check_command_texts() -> Result<()> {
+    for locale in Locale::iter() {
+        let catalog = locale.get_catalog();
+
+        for command in MODULES.iter().flat_map(|module| module.commands) {
+            catalog.get_command(command.name)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn set_my_commands(handler: &Handler) -> Result<()> {
+    let scopes = [
+        (BotCommandScope::AllPrivateChats, CommandScope::Private),
+        (BotCommandScope::AllGroupChats, CommandScope::Group),
+    ];
+
+    let languages = [(None, handler.config.default_locale)].into_iter().chain(
+        LANGUAGES
+            .into_iter()
+            .map(|(language, locale)| (Some(language), locale)),
+    );
+
+    for (bot_scope, scope) in scopes {
+        for (language, locale) in languages.clone() {
+            let catalog = locale.get_catalog();
+
+            let commands = MODULES
+                .iter()
+                .filter(|module| module.is_enabled(&handler.config))
+                .flat_map(|module| module.commands)
+                .filter(|command| command.is_listed_in(scope))
+                .map(|command| {
+                    let command = catalog.get_command(command.name)?;
+                    Ok(BotCommand::new(command.name, command.description)?)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let mut set_my_commands = SetMyCommands::new(commands).scope(bot_scope.clone());
+
+            if let Some(language) = language {
+                set_my_commands = set_my_commands.language_code(language);
+            }
+
+            handler.api.execute(set_my_commands).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,9 @@
-use crate::{database::reversi::Session, Handler};
+use super::registry::{CommandInfo, Module};
+use crate::{
+    database::reversi::Session,
+    i18n::{self, Catalog},
+    Feature, Handler,
+};
 use anyhow::Result;
 use gamie::reversi::{Player, ReversiError};
 use tgbot::{
@@ -9,36 +14,57 @@ use tgbot::{
     },
 };
 
-pub async fn handle_reversi_command(handler: &Handler, command: &Command) -> Result<bool> {
-    if command.get_name() == "/reversi" {
-        let msg = command.get_message();
-        let chat_id = msg.get_chat_id();
-        let msg_id = msg.id;
+pub const MODULE: Module = Module {
+    name: "reversi",
+    feature: Some(Feature::Reversi),
+    commands: &[CommandInfo {
+        name: "reversi",
+        ..CommandInfo::DEFAULT
+    }],
+    callback_prefix: Some("reversi"),
+    handle_command: Some(|handler, command| Box::pin(handle_reversi_command(handler, command))),
+    handle_callback_query: Some(|handler, callback_query| {
+        Box::pin(handle_reversi_callback_query(handler, callback_query))
+    }),
+    ..Module::DEFAULT
+};
+
+pub async fn handle_reversi_command(handler: &Handler, command: &Command) -> Result<()> {
+    let msg = command.get_message();
+    let chat_id = msg.get_chat_id();
+    let msg_id = msg.id;
 
-        let mut pool = handler.database.reversi.lock();
+    let locale = handler.get_locale(chat_id, msg.get_user())?;
+    let catalog = locale.get_catalog();
 
-        let reversi = Session::new();
+    let pool = handler.database.reversi.lock();
 
-        let send_message = SendMessage::new(chat_id, get_game_info(&reversi))
-            .reply_markup(get_inline_keyboard(&reversi))
-            .reply_to_message_id(msg_id);
+    let reversi = Session::new(locale);
 
-        pool.sessions.insert([chat_id, msg_id], reversi);
+    let send_message = SendMessage::new(chat_id, get_game_info(catalog, &reversi))
+        .reply_markup(get_inline_keyboard(&reversi))
+        .reply_to_message_id(msg_id);
 
-        drop(pool);
+    pool.insert([chat_id, msg_id], reversi)?;
 
-        handler.api.execute(send_message).await?;
+    drop(pool);
 
-        return Ok(true);
+    let board_msg = handler.outbox.send(chat_id, send_message).await?;
+
+    let pool = handler.database.reversi.lock();
+
+    if let Some(mut reversi) = pool.get(&[chat_id, msg_id])? {
+        reversi.message = Some(board_msg.id);
+        pool.insert([chat_id, msg_id], reversi)?;
     }
 
-    Ok(false)
+    Ok(())
 }
 
 pub async fn handle_reversi_callback_query(
     handler: &Handler,
     callback_query: &CallbackQuery,
-) -> Result<bool> {
+) -> Result<()> {
     if let CallbackQuery {
         id,
         from: user,
@@ -53,10 +79,12 @@ pub async fn handle_reversi_callback_query(
             let chat_id = msg.get_chat_id();
             let user_id = user.id;
 
+            let catalog = handler.get_catalog(chat_id, Some(user))?;
+
             if row < 8 && col < 8 {
-                let mut pool = handler.database.reversi.lock();
+                let pool = handler.database.reversi.lock();
 
-                if let Some(reversi) = pool.sessions.get_mut(&[chat_id, cmd_msg_id]) {
+                if let Some(mut reversi) = pool.get(&[chat_id, cmd_msg_id])? {
                     let next_player = reversi.game.get_next_player();
 
                     let is_right_player = if reversi
@@ -89,29 +117,36 @@ pub async fn handle_reversi_callback_query(
                     if is_right_player {
                         match reversi.game.place(next_player, row, col) {
                             Ok(()) => {
-                                let edit_message =
-                                    EditMessageText::new(chat_id, msg_id, get_game_info(reversi))
-                                        .reply_markup(get_inline_keyboard(reversi));
+                                let edit_message = EditMessageText::new(
+                                    chat_id,
+                                    msg_id,
+                                    get_game_info(reversi.locale.get_catalog(), &reversi),
+                                )
+                                .reply_markup(get_inline_keyboard(&reversi));
 
                                 let answer_callback_query = AnswerCallbackQuery::new(id);
 
                                 if reversi.game.is_ended() {
-                                    pool.sessions.remove(&[chat_id, cmd_msg_id]);
+                                    pool.remove(&[chat_id, cmd_msg_id])?;
+                                } else {
+                                    pool.insert([chat_id, cmd_msg_id], reversi)?;
                                 }
 
                                 drop(pool);
 
                                 tokio::try_join!(
-                                    handler.api.execute(edit_message),
+                                    handler.outbox.edit(chat_id, msg_id, edit_message),
                                     handler.api.execute(answer_callback_query)
                                 )?;
                             }
                             Err(ReversiError::OccupiedPosition)
                             | Err(ReversiError::InvalidPosition) => {
+                                pool.insert([chat_id, cmd_msg_id], reversi)?;
+
                                 drop(pool);
 
                                 let answer_callback_query = AnswerCallbackQuery::new(id)
-                                    .text("无法在此落子")
+                                    .text(catalog.invalid_move)
                                     .show_alert(true);
 
                                 handler.api.execute(answer_callback_query).await?;
@@ -122,7 +157,7 @@ pub async fn handle_reversi_callback_query(
                         drop(pool);
 
                         let answer_callback_query = AnswerCallbackQuery::new(id)
-                            .text("不是你的回合")
+                            .text(catalog.not_your_turn)
                             .show_alert(true);
 
                         handler.api.execute(answer_callback_query).await?;
@@ -131,18 +166,34 @@ pub async fn handle_reversi_callback_query(
                     drop(pool);
 
                     let answer_callback_query = AnswerCallbackQuery::new(id)
-                        .text("找不到游戏")
+                        .text(catalog.game_not_found)
                         .show_alert(true);
 
                     handler.api.execute(answer_callback_query).await?;
                 }
             }
-
-            return Ok(true);
         }
     }
 
-    Ok(false)
+    Ok(())
+}
+
+pub async fn handle_reversi_expired(
+    handler: &Handler,
+    chat_id: i64,
+    reversi: Session,
+) -> Result<()> {
+    if let Some(msg_id) = reversi.message {
+        let catalog = reversi.locale.get_catalog();
+
+        let mut info = get_players_info(catalog, &reversi);
+        info.push_str(catalog.game_timed_out);
+
+        let edit_message = EditMessageText::new(chat_id, msg_id, info);
+        handler.outbox.edit(chat_id, msg_id, edit_message).await?;
+    }
+
+    Ok(())
 }
 
 fn get_inline_keyboard(reversi: &Session) -> InlineKeyboardMarkup {
@@ -193,23 +244,29 @@ fn parse_callback_data(data: &str) -> Option<(usize, usize)> {
     None
 }
 
-fn get_game_info(reversi: &Session) -> String {
-    let mut info = String::from("黑白棋\n\n");
+fn get_players_info(catalog: &Catalog, reversi: &Session) -> String {
+    let mut info = format!("{}\n\n", catalog.reversi_title);
 
     if let Some((_, player_0)) = &reversi.player_0 {
-        info.push_str("⚫：");
-        info.push_str(player_0);
+        let player = i18n::format(catalog.player, &[("piece", &"⚫"), ("name", player_0)]);
+        info.push_str(&player);
         info.push('\n');
     }
 
     if let Some((_, player_1)) = &reversi.player_1 {
-        info.push_str("⚪：");
-        info.push_str(player_1);
+        let player = i18n::format(catalog.player, &[("piece", &"⚪"), ("name", player_1)]);
+        info.push_str(&player);
         info.push('\n');
     }
 
     info.push('\n');
 
+    info
+}
+
+fn get_game_info(catalog: &Catalog, reversi: &Session) -> String {
+    let mut info = get_players_info(catalog, reversi);
+
     if reversi.game.is_ended() {
         let mut black_factor = 0;
         let mut white_factor = 0;
@@ -224,36 +281,32 @@ fn get_game_info(reversi: &Session) -> String {
             }
         }
 
-        info.push_str("⚫：");
-        info.push_str(&black_factor.to_string());
-        info.push(' ');
-
-        info.push_str("⚪：");
-        info.push_str(&white_factor.to_string());
-        info.push('\n');
+        let score = i18n::format(
+            catalog.reversi_score,
+            &[("black", &black_factor), ("white", &white_factor)],
+        );
 
-        info.push('\n');
+        info.push_str(&score);
+        info.push_str("\n\n");
 
         match reversi.game.get_winner() {
             Some(Player::Player0) => {
                 let (_, player_0) = unsafe { reversi.player_0.as_ref().unwrap_unchecked() };
-                info.push_str(player_0);
-                info.push_str(" 赢了");
+                info.push_str(&i18n::format(catalog.winner, &[("name", player_0)]));
             }
             Some(Player::Player1) => {
                 let (_, player_1) = unsafe { reversi.player_1.as_ref().unwrap_unchecked() };
-                info.push_str(player_1);
-                info.push_str(" 赢了");
+                info.push_str(&i18n::format(catalog.winner, &[("name", player_1)]));
             }
-            None => info.push_str("平局"),
+            None => info.push_str(catalog.draw),
         }
     } else {
-        info.push_str("轮到：");
+        let piece = match reversi.game.get_next_player() {
+            Player::Player0 => "⚫",
+            Player::Player1 => "⚪",
+        };
 
-        match reversi.game.get_next_player() {
-            Player::Player0 => info.push('⚫'),
-            Player::Player1 => info.push('⚪'),
-        }
+        info.push_str(&i18n::format(catalog.next_player, &[("piece", &piece)]));
     }
 
     info
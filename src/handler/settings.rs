@@ -0,0 +1,228 @@
+use super::registry::{CommandInfo, CommandScope, Module, MODULES};
+use crate::{
+    database::settings::{ChatSettings, Trigger},
+    i18n::{self, Catalog, Locale},
+    Feature, Handler,
+};
+use anyhow::Result;
+use tgbot::{
+    methods::{AnswerCallbackQuery, EditMessageText, GetChatMember, SendMessage},
+    types::{
+        CallbackQuery, ChatMember, Command, InlineKeyboardButton, InlineKeyboardButtonKind,
+        InlineKeyboardMarkup,
+    },
+};
+
+pub const MODULE: Module = Module {
+    name: "settings",
+    commands: &[CommandInfo {
+        name: "settings",
+        scope: CommandScope::Group,
+        ..CommandInfo::DEFAULT
+    }],
+    callback_prefix: Some("settings"),
+    handle_command: Some(|handler, command| Box::pin(handle_settings_command(handler, command))),
+    handle_callback_query: Some(|handler, callback_query| {
+        Box::pin(handle_settings_callback_query(handler, callback_query))
+    }),
+    ..Module::DEFAULT
+};
+
+pub async fn handle_settings_command(handler: &Handler, command: &Command) -> Result<()> {
+    let msg = command.get_message();
+    let chat_id = msg.get_chat_id();
+    let msg_id = msg.id;
+
+    let catalog = handler.get_catalog(chat_id, msg.get_user())?;
+
+    let send_message = if is_chat_admin(handler, chat_id, msg.get_user_id()).await? {
+        let settings = handler
+            .database
+            .settings
+            .lock()
+            .get(&chat_id)?
+            .unwrap_or_default();
+
+        SendMessage::new(chat_id, catalog.settings_prompt)
+            .reply_markup(get_inline_keyboard(handler, catalog, &settings))
+            .reply_to_message_id(msg_id)
+    } else {
+        SendMessage::new(chat_id, catalog.settings_admin_only).reply_to_message_id(msg_id)
+    };
+
+    handler.outbox.send(chat_id, send_message).await?;
+
+    Ok(())
+}
+
+pub async fn handle_settings_callback_query(
+    handler: &Handler,
+    callback_query: &CallbackQuery,
+) -> Result<()> {
+    if let CallbackQuery {
+        id,
+        from: user,
+        message: Some(msg),
+        data: Some(cb_data),
+        ..
+    } = callback_query
+    {
+        if let Some(data) = parse_callback_data(cb_data) {
+            let chat_id = msg.get_chat_id();
+            let msg_id = msg.id;
+
+            if !is_chat_admin(handler, chat_id, Some(user.id)).await? {
+                let catalog = handler.get_catalog(chat_id, Some(user))?;
+
+                let answer_callback_query = AnswerCallbackQuery::new(id)
+                    .text(catalog.settings_admin_only)
+                    .show_alert(true);
+
+                handler.api.execute(answer_callback_query).await?;
+
+                return Ok(());
+            }
+
+            let pool = handler.database.settings.lock();
+
+            let mut settings = pool.get(&chat_id)?.unwrap_or_default();
+
+            match data {
+                CallbackData::Toggle(trigger, feature) => settings.toggle(trigger, feature),
+                CallbackData::Language(language) => settings.language = language,
+            }
+
+            pool.insert(chat_id, settings.clone())?;
+
+            drop(pool);
+
+            let catalog = handler.get_catalog(chat_id, Some(user))?;
+
+            let edit_message = EditMessageText::new(chat_id, msg_id, catalog.settings_prompt)
+                .reply_markup(get_inline_keyboard(handler, catalog, &settings));
+
+            let answer_callback_query = AnswerCallbackQuery::new(id);
+
+            tokio::try_join!(
+                handler.outbox.edit(chat_id, msg_id, edit_message),
+                handler.api.execute(answer_callback_query)
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn is_chat_admin(handler: &Handler, chat_id: i64, user_id: Option<i64>) -> Result<bool> {
+    let user_id = match user_id {
+        Some(user_id) => user_id,
+        None => return Ok(false),
+    };
+
+    if chat_id == user_id || Some(user_id) == handler.config.owner {
+        return Ok(true);
+    }
+
+    let get_chat_member = GetChatMember::new(chat_id, user_id);
+
+    Ok(matches!(
+        handler.api.execute(get_chat_member).await?,
+        ChatMember::Administrator(_) | ChatMember::Creator(_)
+    ))
+}
+
+fn get_inline_keyboard(
+    handler: &Handler,
+    catalog: &Catalog,
+    settings: &ChatSettings,
+) -> InlineKeyboardMarkup {
+    let commands = MODULES.iter().filter_map(|module| {
+        let command = module.commands.first()?;
+        Some((
+            Trigger::Command,
+            module.feature?,
+            format!("/{}", command.name),
+        ))
+    });
+
+    let keywords = MODULES.iter().filter_map(|module| {
+        if module.keywords.is_empty() {
+            return None;
+        }
+
+        let keywords = module.keywords.join(catalog.list_separator);
+        let label = i18n::format(catalog.settings_keyword, &[("keywords", &keywords)]);
+        Some((Trigger::Keyword, module.feature?, label))
+    });
+
+    let mut vec = commands
+        .chain(keywords)
+        .filter(|(_, feature, _)| handler.config.is_enabled(*feature))
+        .map(|(trigger, feature, label)| {
+            let status = if settings.is_enabled(trigger, feature) {
+                "✅"
+            } else {
+                "❌"
+            };
+
+            vec![InlineKeyboardButton::new(
+                format!("{status} {label}"),
+                InlineKeyboardButtonKind::CallbackData(format!(
+                    "settings-{}-{}",
+                    trigger.as_name(),
+                    feature.as_name()
+                )),
+            )]
+        })
+        .collect::<Vec<_>>();
+
+    let get_language_button = |language: Option<Locale>, label: &str| {
+        let label = if settings.language == language {
+            format!("✅ {label}")
+        } else {
+            label.to_owned()
+        };
+
+        InlineKeyboardButton::new(
+            label,
+            InlineKeyboardButtonKind::CallbackData(format!(
+                "settings-language-{}",
+                language.map_or("auto", |language| language.as_name())
+            )),
+        )
+    };
+
+    vec.push(vec![get_language_button(
+        None,
+        catalog.settings_auto_language,
+    )]);
+
+    vec.push(
+        Locale::iter()
+            .map(|locale| get_language_button(Some(locale), locale.get_catalog().language_name))
+            .collect(),
+    );
+
+    InlineKeyboardMarkup::from_vec(vec)
+}
+
+enum CallbackData {
+    Toggle(Trigger, Feature),
+    Language(Option<Locale>),
+}
+
+fn parse_callback_data(data: &str) -> Option<CallbackData> {
+    let mut data = data.splitn(3, '-');
+
+    match (data.next(), data.next(), data.next()) {
+        (Some("settings"), Some("language"), Some("auto")) => Some(CallbackData::Language(None)),
+        (Some("settings"), Some("language"), Some(language)) => {
+            Some(CallbackData::Language(Some(Locale::from_name(language)?)))
+        }
+        (Some("settings"), Some(trigger), Some(feature)) => Some(CallbackData::Toggle(
+            Trigger::from_name(trigger)?,
+            Feature::from_name(feature)?,
+        )),
+        _ => None,
+    }
+}
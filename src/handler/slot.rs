@@ -1,38 +1,41 @@
-use crate::Handler;
+use super::registry::{CommandInfo, Module};
+use crate::{Feature, Handler};
 use anyhow::Result;
 use tgbot::{
     methods::SendDice,
     types::{Command, DiceKind, Message},
 };
 
-pub async fn handle_slot_message(handler: &Handler, message: &Message) -> Result<bool> {
-    if let Some(text) = message.get_text() {
-        if text.data.contains("老虎机") {
-            let chat_id = message.get_chat_id();
-            let msg_id = message.id;
+pub const MODULE: Module = Module {
+    name: "slot",
+    feature: Some(Feature::Slot),
+    commands: &[CommandInfo {
+        name: "slot",
+        ..CommandInfo::DEFAULT
+    }],
+    keywords: &["老虎机"],
+    handle_command: Some(|handler, command| Box::pin(handle_slot_command(handler, command))),
+    handle_keyword: Some(|handler, message| Box::pin(handle_slot_keyword(handler, message))),
+    ..Module::DEFAULT
+};
 
-            let send_dice =
-                SendDice::new(chat_id, DiceKind::SlotMachine).reply_to_message_id(msg_id);
-            handler.api.execute(send_dice).await?;
+pub async fn handle_slot_keyword(handler: &Handler, message: &Message) -> Result<()> {
+    let chat_id = message.get_chat_id();
+    let msg_id = message.id;
 
-            return Ok(true);
-        }
-    }
+    let send_dice = SendDice::new(chat_id, DiceKind::SlotMachine).reply_to_message_id(msg_id);
+    handler.outbox.send(chat_id, send_dice).await?;
 
-    Ok(false)
+    Ok(())
 }
 
-pub async fn handle_slot_command(handler: &Handler, command: &Command) -> Result<bool> {
-    if command.get_name() == "/slot" {
-        let msg = command.get_message();
-        let chat_id = msg.get_chat_id();
-        let msg_id = msg.id;
-
-        let send_dice = SendDice::new(chat_id, DiceKind::SlotMachine).reply_to_message_id(msg_id);
-        handler.api.execute(send_dice).await?;
+pub async fn handle_slot_command(handler: &Handler, command: &Command) -> Result<()> {
+    let msg = command.get_message();
+    let chat_id = msg.get_chat_id();
+    let msg_id = msg.id;
 
-        return Ok(true);
-    }
+    let send_dice = SendDice::new(chat_id, DiceKind::SlotMachine).reply_to_message_id(msg_id);
+    handler.outbox.send(chat_id, send_dice).await?;
 
-    Ok(false)
+    Ok(())
 }
@@ -0,0 +1,103 @@
+use crate::{database::ocr::Language, Config, Database, Feature};
+use leptess::LepTess;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+pub const HEARTBEAT_PERIOD: Duration = Duration::from_secs(30);
+
+pub struct Health {
+    last_api_success: Mutex<Option<Instant>>,
+    gc_timeout: Duration,
+    ocr: Vec<(Language, bool)>,
+}
+
+impl Health {
+    pub fn new(config: &Config) -> Self {
+        let ocr = if config.is_enabled(Feature::Ocr) {
+            config
+                .ocr_languages
+                .iter()
+                .map(|lang| {
+                    let res = LepTess::new(
+                        config.ocr_data_path.as_deref(),
+                        lang.as_tesseract_data_str(),
+                    );
+
+                    if let Err(err) = &res {
+                        warn!(
+                            language = lang.as_tesseract_data_str(),
+                            error = %err,
+                            "Failed to initialize Tesseract"
+                        );
+                    }
+
+                    (*lang, res.is_ok())
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            last_api_success: Mutex::new(None),
+            gc_timeout: config.gc_period * 3,
+            ocr,
+        }
+    }
+
+    pub fn api_succeeded(&self) {
+        *self.last_api_success.lock() = Some(Instant::now());
+    }
+
+    pub fn report(&self, database: &Database) -> Report {
+        let api_elapsed = self.last_api_success.lock().map(|time| time.elapsed());
+        let api = Check {
+            ok: api_elapsed.map_or(false, |elapsed| elapsed < HEARTBEAT_PERIOD * 3),
+            elapsed_secs: api_elapsed.map(|elapsed| elapsed.as_secs()),
+        };
+
+        let gc_elapsed = database.last_gc().elapsed();
+        let gc = Check {
+            ok: gc_elapsed < self.gc_timeout,
+            elapsed_secs: Some(gc_elapsed.as_secs()),
+        };
+
+        let ocr = self
+            .ocr
+            .iter()
+            .map(|(lang, ok)| OcrCheck {
+                language: lang.as_tesseract_data_str(),
+                ok: *ok,
+            })
+            .collect::<Vec<_>>();
+
+        Report {
+            ok: api.ok && gc.ok && ocr.iter().all(|check| check.ok),
+            api,
+            gc,
+            ocr,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Report {
+    pub ok: bool,
+    api: Check,
+    gc: Check,
+    ocr: Vec<OcrCheck>,
+}
+
+#[derive(Serialize)]
+struct Check {
+    ok: bool,
+    elapsed_secs: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct OcrCheck {
+    language: &'static str,
+    ok: bool,
+}
@@ -0,0 +1,180 @@
+use super::{Catalog, CommandText};
+
+pub const CATALOG: Catalog = Catalog {
+    language_name: "English",
+    list_separator: ", ",
+    commands: &[
+        CommandText {
+            name: "about",
+            args: "",
+            description: "About this bot",
+            usage: "Show a short introduction and the source code link",
+        },
+        CommandText {
+            name: "admin",
+            args: "",
+            description: "Manage the bot",
+            usage: "",
+        },
+        CommandText {
+            name: "connectfour",
+            args: "",
+            description: "Play Connect Four",
+            usage: "Start a game of Connect Four between the first two users to tap the board\nTap the board to drop a disc into that column. The first to connect four in a row, column or diagonal wins",
+        },
+        CommandText {
+            name: "dart",
+            args: "",
+            description: "Throw a dart",
+            usage: "",
+        },
+        CommandText {
+            name: "dice",
+            args: "",
+            description: "Roll a die",
+            usage: "",
+        },
+        CommandText {
+            name: "help",
+            args: "[command]",
+            description: "Show help",
+            usage: "List the commands available in this chat, or show detailed help for a command",
+        },
+        CommandText {
+            name: "minesweeper",
+            args: "[height] [width] [mines]",
+            description: "Play Minesweeper",
+            usage: "Without arguments, the board is 8×8 with 9 mines\nHeight and width are from 1 to 8, and mines are at most (height - 1) × (width - 1)",
+        },
+        CommandText {
+            name: "ocr",
+            args: "",
+            description: "Recognize text in an image",
+            usage: "Choose a language, then reply to the bot's prompt with an image to recognize its text\nOnly the user who sent the command can choose the language and send the image",
+        },
+        CommandText {
+            name: "reversi",
+            args: "",
+            description: "Play Reversi",
+            usage: "Start a game of Reversi between the first two users to tap the board\nWhen the board is full or neither player can move, the player with more discs wins",
+        },
+        CommandText {
+            name: "settings",
+            args: "",
+            description: "Toggle commands and keywords in this chat (group admins)",
+            usage: "Send the settings panel to toggle commands and keyword triggers, or set the language of this chat\nIn groups, only admins can change settings",
+        },
+        CommandText {
+            name: "slot",
+            args: "",
+            description: "Spin the slot machine",
+            usage: "",
+        },
+        CommandText {
+            name: "start",
+            args: "",
+            description: "Get started",
+            usage: "",
+        },
+        CommandText {
+            name: "tictactoe",
+            args: "",
+            description: "Play Tic-Tac-Toe",
+            usage: "Start a game of Tic-Tac-Toe between the first two users to tap the board\nThe first to get three in a row, column or diagonal wins",
+        },
+    ],
+
+    rate_limited: "Too many requests, please try again later",
+    game_not_found: "Game not found",
+    not_your_turn: "It's not your turn",
+    invalid_move: "You can't move here",
+    game_timed_out: "Game timed out",
+    player: "{piece}: {name}",
+    winner: "{name} wins",
+    draw: "Draw",
+    next_player: "Next: {piece}",
+
+    about: r#"
+eaimty_bot
+
+A personal Telegram bot
+
+Get help with /help
+
+Source code: https://github.com/EAimTY/eaimty_bot
+"#,
+    start: r#"
+eaimty_bot
+
+A personal Telegram bot
+
+Get help with /help
+"#,
+
+    agree_no: "No",
+    agree_pass: "OK, no objections, approved!",
+
+    help_hint: "Send /help <command> for detailed help",
+    help_unknown_command: "Unknown command: /{name}",
+    help_disabled_command: "/{name} is disabled in this chat",
+    help_keywords: "Keywords: {keywords}",
+
+    settings_prompt: "Tap the buttons to toggle commands and keywords in this chat, or set the language of this chat",
+    settings_admin_only: "Only group admins can change settings",
+    settings_keyword: "Keyword \"{keywords}\"",
+    settings_auto_language: "User's language",
+
+    connectfour_title: "Connect Four",
+
+    minesweeper_title: "Minesweeper",
+    minesweeper_invalid_args: "Invalid arguments",
+    minesweeper_steps: "{name}: {steps} moves",
+    minesweeper_time: "Time: {minutes} min {seconds} s",
+    minesweeper_win: "All mines cleared",
+    minesweeper_exploded: "{name} hit a mine",
+
+    ocr_select_language: "Choose the OCR language",
+    ocr_send_picture: "Language: {lang}. Reply to this message with the image to recognize (sent as a photo)",
+    ocr_reselect: "Choose again",
+    ocr_not_initiator: "You didn't send this command",
+    ocr_session_not_found: "Session not found",
+    ocr_timed_out: "OCR session timed out",
+    ocr_download_failed: "Failed to get the image",
+
+    reversi_title: "Reversi",
+    reversi_score: "⚫: {black} ⚪: {white}",
+
+    tictactoe_title: "Tic-Tac-Toe",
+
+    admin_usage: r#"
+/admin stats - Status
+/admin sessions <game> - List active sessions
+/admin kill <chat ID> <message ID> - End a session
+/admin broadcast <text> - Send a message to the broadcast chats
+/admin access - List access rules
+/admin allow <chat|user> <ID> - Add to the allow list
+/admin deny <chat|user> <ID> - Add to the deny list
+/admin unlist <chat|user> <ID> - Remove from the allow or deny list
+"#,
+    admin_uptime: "Uptime: {days} d {hours} h {minutes} min {seconds} s",
+    admin_sessions: "Sessions:",
+    admin_updates: "Updates:",
+    admin_count: "{name}: {count}",
+    admin_unknown_game: "Unknown game: {name}",
+    admin_no_sessions: "No active sessions",
+    admin_session_list: "{count} sessions (chat ID, message ID, idle time):",
+    admin_session: "{chat_id} {msg_id} {idle} s",
+    admin_session_killed: "Ended {name} session",
+    admin_session_not_found: "Session not found",
+    admin_no_rules: "No access rules",
+    admin_rule_list: "Access rules:",
+    admin_rule_fixed: " (config file)",
+    admin_rule_not_editable: "The rule for {subject} is set in the config file and can't be changed",
+    admin_rule_not_persistent: "Access rules can't be changed by command without storage or state_file set, as they would be lost on restart",
+    admin_rule_set: "Set {rule} {subject}",
+    admin_rule_removed: "Removed the rule for {subject}",
+    admin_no_broadcast_chats: "No broadcast chats set",
+    admin_broadcast_sent: "Sent to {count} chats",
+    admin_broadcast_failed: "Sent to {count} chats, failed:\n{errors}",
+    admin_broadcast_error: "{chat_id} ({error})",
+};
@@ -0,0 +1,180 @@
+use super::{Catalog, CommandText};
+
+pub const CATALOG: Catalog = Catalog {
+    language_name: "日本語",
+    list_separator: "、",
+    commands: &[
+        CommandText {
+            name: "about",
+            args: "",
+            description: "このボットについて",
+            usage: "ボットの紹介とソースコードの URL を表示します",
+        },
+        CommandText {
+            name: "admin",
+            args: "",
+            description: "ボットを管理する",
+            usage: "",
+        },
+        CommandText {
+            name: "connectfour",
+            args: "",
+            description: "四目並べで遊ぶ",
+            usage: "最初に盤面をタップした二人で四目並べを始めます\n盤面をタップするとその列に駒を落とします。縦・横・斜めに四つ並べた方の勝ちです",
+        },
+        CommandText {
+            name: "dart",
+            args: "",
+            description: "ダーツを投げる",
+            usage: "",
+        },
+        CommandText {
+            name: "dice",
+            args: "",
+            description: "サイコロを振る",
+            usage: "",
+        },
+        CommandText {
+            name: "help",
+            args: "[コマンド]",
+            description: "ヘルプ",
+            usage: "引数なしでこのチャットで使えるコマンドを一覧表示し、コマンド名を付けるとそのコマンドの詳細を表示します",
+        },
+        CommandText {
+            name: "minesweeper",
+            args: "[高さ] [幅] [地雷の数]",
+            description: "マインスイーパーで遊ぶ",
+            usage: "引数なしでは 8×8 の盤面に地雷 9 個です\n高さと幅は 1 以上 8 以下、地雷の数は（高さ - 1）×（幅 - 1）以下です",
+        },
+        CommandText {
+            name: "ocr",
+            args: "",
+            description: "画像の文字を認識する",
+            usage: "言語を選んだ後、ボットの案内メッセージに画像で返信すると文字を認識します\nコマンドを送ったユーザーだけが言語の選択と画像の送信をできます",
+        },
+        CommandText {
+            name: "reversi",
+            args: "",
+            description: "リバーシで遊ぶ",
+            usage: "最初に盤面をタップした二人でリバーシを始めます\n盤面が埋まるか両者とも打てなくなったとき、石の多い方の勝ちです",
+        },
+        CommandText {
+            name: "settings",
+            args: "",
+            description: "このチャットのコマンドとキーワードを切り替える（グループ管理者）",
+            usage: "設定パネルを送信します。ボタンでコマンドとキーワードの有効・無効を切り替えたり、このチャットの言語を設定したりできます\nグループでは管理者のみ変更できます",
+        },
+        CommandText {
+            name: "slot",
+            args: "",
+            description: "スロットを回す",
+            usage: "",
+        },
+        CommandText {
+            name: "start",
+            args: "",
+            description: "はじめる",
+            usage: "",
+        },
+        CommandText {
+            name: "tictactoe",
+            args: "",
+            description: "三目並べで遊ぶ",
+            usage: "最初に盤面をタップした二人で三目並べを始めます\n縦・横・斜めに三つ並べた方の勝ちです",
+        },
+    ],
+
+    rate_limited: "操作が多すぎます。しばらくしてからもう一度お試しください",
+    game_not_found: "ゲームが見つかりません",
+    not_your_turn: "あなたの番ではありません",
+    invalid_move: "ここには置けません",
+    game_timed_out: "ゲームがタイムアウトしました",
+    player: "{piece}：{name}",
+    winner: "{name} の勝ち",
+    draw: "引き分け",
+    next_player: "手番：{piece}",
+
+    about: r#"
+eaimty_bot
+
+個人用 Telegram ボット
+
+ヘルプ /help
+
+ソースコード：https://github.com/EAimTY/eaimty_bot
+"#,
+    start: r#"
+eaimty_bot
+
+個人用 Telegram ボット
+
+ヘルプ /help
+"#,
+
+    agree_no: "ありません",
+    agree_pass: "はい、異議なし、可決！",
+
+    help_hint: "/help <コマンド> でコマンドの詳細を表示します",
+    help_unknown_command: "不明なコマンド：/{name}",
+    help_disabled_command: "/{name} はこのチャットでは無効です",
+    help_keywords: "キーワード：{keywords}",
+
+    settings_prompt: "ボタンをタップしてこのチャットのコマンドとキーワードを切り替えるか、このチャットの言語を設定してください",
+    settings_admin_only: "設定を変更できるのはグループ管理者のみです",
+    settings_keyword: "キーワード「{keywords}」",
+    settings_auto_language: "ユーザーの言語",
+
+    connectfour_title: "四目並べ",
+
+    minesweeper_title: "マインスイーパー",
+    minesweeper_invalid_args: "引数が正しくありません",
+    minesweeper_steps: "{name}：{steps} 手",
+    minesweeper_time: "時間：{minutes} 分 {seconds} 秒",
+    minesweeper_win: "クリア",
+    minesweeper_exploded: "{name} が地雷を踏みました",
+
+    ocr_select_language: "OCR の言語を選んでください",
+    ocr_send_picture: "言語：{lang}。認識する画像でこのメッセージに返信してください（写真として送信）",
+    ocr_reselect: "選び直す",
+    ocr_not_initiator: "コマンドを送ったユーザーではありません",
+    ocr_session_not_found: "セッションが見つかりません",
+    ocr_timed_out: "OCR セッションがタイムアウトしました",
+    ocr_download_failed: "画像を取得できませんでした",
+
+    reversi_title: "リバーシ",
+    reversi_score: "⚫：{black} ⚪：{white}",
+
+    tictactoe_title: "三目並べ",
+
+    admin_usage: r#"
+/admin stats - 稼働状況
+/admin sessions <ゲーム> - 進行中のセッションを一覧表示
+/admin kill <チャット ID> <メッセージ ID> - セッションを終了
+/admin broadcast <内容> - ブロードキャスト先にメッセージを送信
+/admin access - アクセスルールを一覧表示
+/admin allow <chat|user> <ID> - 許可リストに追加
+/admin deny <chat|user> <ID> - 拒否リストに追加
+/admin unlist <chat|user> <ID> - 許可リストまたは拒否リストから削除
+"#,
+    admin_uptime: "稼働時間：{days} 日 {hours} 時間 {minutes} 分 {seconds} 秒",
+    admin_sessions: "セッション：",
+    admin_updates: "アップデート：",
+    admin_count: "{name}：{count}",
+    admin_unknown_game: "不明なゲーム：{name}",
+    admin_no_sessions: "進行中のセッションはありません",
+    admin_session_list: "全 {count} 件のセッション（チャット ID メッセージ ID 待機時間）：",
+    admin_session: "{chat_id} {msg_id} {idle} 秒",
+    admin_session_killed: "{name} のセッションを終了しました",
+    admin_session_not_found: "セッションが見つかりません",
+    admin_no_rules: "アクセスルールはありません",
+    admin_rule_list: "アクセスルール：",
+    admin_rule_fixed: "（設定ファイル）",
+    admin_rule_not_editable: "{subject} のルールは設定ファイルで指定されているため変更できません",
+    admin_rule_not_persistent: "storage または state_file が設定されていないため、再起動後にアクセスルールを保持できず、コマンドで変更できません",
+    admin_rule_set: "{rule} {subject} を設定しました",
+    admin_rule_removed: "{subject} のルールを削除しました",
+    admin_no_broadcast_chats: "ブロードキャスト先が設定されていません",
+    admin_broadcast_sent: "{count} 件のチャットに送信しました",
+    admin_broadcast_failed: "{count} 件のチャットに送信しました。送信失敗：\n{errors}",
+    admin_broadcast_error: "{chat_id}（{error}）",
+};
@@ -0,0 +1,213 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Write};
+
+mod en;
+mod ja;
+mod zh_hans;
+mod zh_hant;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    #[serde(rename = "zh-Hans")]
+    ZhHans,
+    #[serde(rename = "zh-Hant")]
+    ZhHant,
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "ja")]
+    Ja,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::ZhHans
+    }
+}
+
+impl Locale {
+    pub fn iter() -> impl Iterator<Item = Self> {
+        [Self::ZhHans, Self::ZhHant, Self::En, Self::Ja].into_iter()
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        Self::iter().find(|locale| locale.as_name() == s)
+    }
+
+    pub fn as_name(&self) -> &'static str {
+        match self {
+            Self::ZhHans => "zh-Hans",
+            Self::ZhHant => "zh-Hant",
+            Self::En => "en",
+            Self::Ja => "ja",
+        }
+    }
+
+    pub fn from_language_code(code: &str) -> Option<Self> {
+        let code = code.to_ascii_lowercase();
+        let mut subtags = code.split(['-', '_']);
+
+        match (subtags.next(), subtags.next()) {
+            (Some("zh"), Some("hant" | "tw" | "hk" | "mo")) => Some(Self::ZhHant),
+            (Some("zh"), _) => Some(Self::ZhHans),
+            (Some("en"), _) => Some(Self::En),
+            (Some("ja"), _) => Some(Self::Ja),
+            _ => None,
+        }
+    }
+
+    pub fn get_catalog(&self) -> &'static Catalog {
+        match self {
+            Self::ZhHans => &zh_hans::CATALOG,
+            Self::ZhHant => &zh_hant::CATALOG,
+            Self::En => &en::CATALOG,
+            Self::Ja => &ja::CATALOG,
+        }
+    }
+}
+
+pub struct Catalog {
+    pub language_name: &'static str,
+    pub list_separator: &'static str,
+    pub commands: &'static [CommandText],
+
+    pub rate_limited: &'static str,
+    pub game_not_found: &'static str,
+    pub not_your_turn: &'static str,
+    pub invalid_move: &'static str,
+    pub game_timed_out: &'static str,
+    pub player: &'static str,
+    pub winner: &'static str,
+    pub draw: &'static str,
+    pub next_player: &'static str,
+
+    pub about: &'static str,
+    pub start: &'static str,
+
+    pub agree_no: &'static str,
+    pub agree_pass: &'static str,
+
+    pub help_hint: &'static str,
+    pub help_unknown_command: &'static str,
+    pub help_disabled_command: &'static str,
+    pub help_keywords: &'static str,
+
+    pub settings_prompt: &'static str,
+    pub settings_admin_only: &'static str,
+    pub settings_keyword: &'static str,
+    pub settings_auto_language: &'static str,
+
+    pub connectfour_title: &'static str,
+
+    pub minesweeper_title: &'static str,
+    pub minesweeper_invalid_args: &'static str,
+    pub minesweeper_steps: &'static str,
+    pub minesweeper_time: &'static str,
+    pub minesweeper_win: &'static str,
+    pub minesweeper_exploded: &'static str,
+
+    pub ocr_select_language: &'static str,
+    pub ocr_send_picture: &'static str,
+    pub ocr_reselect: &'static str,
+    pub ocr_not_initiator: &'static str,
+    pub ocr_session_not_found: &'static str,
+    pub ocr_timed_out: &'static str,
+    pub ocr_download_failed: &'static str,
+
+    pub reversi_title: &'static str,
+    pub reversi_score: &'static str,
+
+    pub tictactoe_title: &'static str,
+
+    pub admin_usage: &'static str,
+    pub admin_uptime: &'static str,
+    pub admin_sessions: &'static str,
+    pub admin_updates: &'static str,
+    pub admin_count: &'static str,
+    pub admin_unknown_game: &'static str,
+    pub admin_no_sessions: &'static str,
+    pub admin_session_list: &'static str,
+    pub admin_session: &'static str,
+    pub admin_session_killed: &'static str,
+    pub admin_session_not_found: &'static str,
+    pub admin_no_rules: &'static str,
+    pub admin_rule_list: &'static str,
+    pub admin_rule_fixed: &'static str,
+    pub admin_rule_not_editable: &'static str,
+    pub admin_rule_not_persistent: &'static str,
+    pub admin_rule_set: &'static str,
+    pub admin_rule_removed: &'static str,
+    pub admin_no_broadcast_chats: &'static str,
+    pub admin_broadcast_sent: &'static str,
+    pub admin_broadcast_failed: &'static str,
+    pub admin_broadcast_error: &'static str,
+}
+
+impl Catalog {
+    pub fn get_command(&self, name: &str) -> Result<&CommandText> {
+        self.commands
+            .iter()
+            .find(|command| command.name == name)
+            .ok_or_else(|| anyhow!("No {} text for command /{name}", self.language_name))
+    }
+}
+
+pub struct CommandText {
+    pub name: &'static str,
+    pub args: &'static str,
+    pub description: &'static str,
+    pub usage: &'static str,
+}
+
+pub fn format(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let arg = rest.find('}').and_then(|end| {
+            let (_, value) = args.iter().find(|(key, _)| *key == &rest[1..end])?;
+            Some((end, value))
+        });
+
+        match arg {
+            Some((end, value)) => {
+                let _ = write!(text, "{value}");
+                rest = &rest[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    text.push_str(rest);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_does_not_expand_inserted_values() {
+        let name = "{steps} {name}";
+        let text = format(
+            en::CATALOG.minesweeper_steps,
+            &[("name", &name), ("steps", &3)],
+        );
+
+        assert_eq!(text, "{steps} {name}: 3 moves");
+    }
+
+    #[test]
+    fn format_keeps_unknown_placeholders() {
+        assert_eq!(
+            format("{a} {b} {", &[("a", &"{b}"), ("c", &1)]),
+            "{b} {b} {"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use super::{Catalog, CommandText};
+
+pub const CATALOG: Catalog = Catalog {
+    language_name: "简体中文",
+    list_separator: "、",
+    commands: &[
+        CommandText {
+            name: "about",
+            args: "",
+            description: "关于",
+            usage: "显示机器人简介与源代码地址",
+        },
+        CommandText {
+            name: "admin",
+            args: "",
+            description: "管理机器人",
+            usage: "",
+        },
+        CommandText {
+            name: "connectfour",
+            args: "",
+            description: "玩四子棋",
+            usage: "发起一局四子棋，由最先点击棋盘的两位用户对战\n点击棋盘即可在对应列放置棋子，横、竖或斜向先连成四子者获胜",
+        },
+        CommandText {
+            name: "dart",
+            args: "",
+            description: "掷一枚飞标",
+            usage: "",
+        },
+        CommandText {
+            name: "dice",
+            args: "",
+            description: "掷一枚骰子",
+            usage: "",
+        },
+        CommandText {
+            name: "help",
+            args: "[命令]",
+            description: "帮助信息",
+            usage: "不带参数时列出本会话中可用的命令，带命令名时显示该命令的详细说明",
+        },
+        CommandText {
+            name: "minesweeper",
+            args: "[棋盘高] [棋盘宽] [地雷数]",
+            description: "玩扫雷",
+            usage: "不带参数时使用 8×8 棋盘与 9 个地雷\n棋盘高与棋盘宽均为 1 至 8，地雷数不超过（棋盘高 - 1）×（棋盘宽 - 1）",
+        },
+        CommandText {
+            name: "ocr",
+            args: "",
+            description: "识别图片中文字",
+            usage: "选择目标语言后，回复机器人的提示消息发送图片，即可识别图片中的文字\n仅发起命令的用户可以选择语言与发送图片",
+        },
+        CommandText {
+            name: "reversi",
+            args: "",
+            description: "玩黑白棋",
+            usage: "发起一局黑白棋，由最先点击棋盘的两位用户对战\n棋盘填满或双方均无处落子时，棋子多者获胜",
+        },
+        CommandText {
+            name: "settings",
+            args: "",
+            description: "开启或关闭本会话中的命令与关键词（群组管理员）",
+            usage: "发送设置面板，点击按钮开启或关闭本会话中的命令与关键词触发，或设置本会话使用的语言\n群组中仅管理员可以修改",
+        },
+        CommandText {
+            name: "slot",
+            args: "",
+            description: "转一次老虎机",
+            usage: "",
+        },
+        CommandText {
+            name: "start",
+            args: "",
+            description: "开始使用",
+            usage: "",
+        },
+        CommandText {
+            name: "tictactoe",
+            args: "",
+            description: "玩 Tic-Tac-Toe",
+            usage: "发起一局 Tic-Tac-Toe，由最先点击棋盘的两位用户对战\n横、竖或斜向先连成三子者获胜",
+        },
+    ],
+
+    rate_limited: "操作过于频繁，请稍后再试",
+    game_not_found: "找不到游戏",
+    not_your_turn: "不是你的回合",
+    invalid_move: "无法在此落子",
+    game_timed_out: "游戏已超时",
+    player: "{piece}：{name}",
+    winner: "{name} 赢了",
+    draw: "平局",
+    next_player: "轮到：{piece}",
+
+    about: r#"
+eaimty_bot
+
+个人用 Telegram Bot
+
+获取帮助信息 /help
+
+源代码：https://github.com/EAimTY/eaimty_bot
+"#,
+    start: r#"
+eaimty_bot
+
+个人用 Telegram Bot
+
+获取帮助信息 /help
+"#,
+
+    agree_no: "没有",
+    agree_pass: "好，没有，通过！",
+
+    help_hint: "发送 /help <命令> 查看命令的详细说明",
+    help_unknown_command: "未知命令：/{name}",
+    help_disabled_command: "/{name} 已在本会话中关闭",
+    help_keywords: "关键词：{keywords}",
+
+    settings_prompt: "点击按钮开启或关闭本会话中的命令与关键词，或设置本会话使用的语言",
+    settings_admin_only: "仅群组管理员可修改设置",
+    settings_keyword: "关键词「{keywords}」",
+    settings_auto_language: "跟随用户语言",
+
+    connectfour_title: "四子棋",
+
+    minesweeper_title: "扫雷",
+    minesweeper_invalid_args: "参数错误",
+    minesweeper_steps: "{name}：{steps} 次操作",
+    minesweeper_time: "用时：{minutes} 分 {seconds} 秒",
+    minesweeper_win: "扫雷成功",
+    minesweeper_exploded: "{name} 引爆了地雷",
+
+    ocr_select_language: "请选择 OCR 目标语言",
+    ocr_send_picture: "目标语言：{lang}，请以需要识别的图片回复此条消息（以图片方式发送）",
+    ocr_reselect: "重新选择",
+    ocr_not_initiator: "不是命令触发者",
+    ocr_session_not_found: "找不到会话",
+    ocr_timed_out: "OCR 会话已超时",
+    ocr_download_failed: "图片获取失败",
+
+    reversi_title: "黑白棋",
+    reversi_score: "⚫：{black} ⚪：{white}",
+
+    tictactoe_title: "Tic-Tac-Toe",
+
+    admin_usage: r#"
+/admin stats - 运行状态
+/admin sessions <游戏> - 列出进行中的会话
+/admin kill <会话 ID> <消息 ID> - 结束会话
+/admin broadcast <内容> - 向广播会话发送消息
+/admin access - 列出访问规则
+/admin allow <chat|user> <ID> - 加入白名单
+/admin deny <chat|user> <ID> - 加入黑名单
+/admin unlist <chat|user> <ID> - 移出白名单或黑名单
+"#,
+    admin_uptime: "运行时间：{days} 天 {hours} 小时 {minutes} 分 {seconds} 秒",
+    admin_sessions: "会话：",
+    admin_updates: "更新：",
+    admin_count: "{name}：{count}",
+    admin_unknown_game: "未知游戏：{name}",
+    admin_no_sessions: "没有进行中的会话",
+    admin_session_list: "共 {count} 个会话（会话 ID 消息 ID 空闲时间）：",
+    admin_session: "{chat_id} {msg_id} {idle} 秒",
+    admin_session_killed: "已结束 {name} 会话",
+    admin_session_not_found: "找不到会话",
+    admin_no_rules: "没有访问规则",
+    admin_rule_list: "访问规则：",
+    admin_rule_fixed: "（配置文件）",
+    admin_rule_not_editable: "{subject} 的规则由配置文件设置，无法修改",
+    admin_rule_not_persistent: "未设置 storage 或 state_file，访问规则无法在重启后保留，因此无法通过命令修改",
+    admin_rule_set: "已设置 {rule} {subject}",
+    admin_rule_removed: "已移除 {subject} 的规则",
+    admin_no_broadcast_chats: "未设置广播会话",
+    admin_broadcast_sent: "已发送至 {count} 个会话",
+    admin_broadcast_failed: "已发送至 {count} 个会话，发送失败：\n{errors}",
+    admin_broadcast_error: "{chat_id}（{error}）",
+};
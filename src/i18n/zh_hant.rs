@@ -0,0 +1,180 @@
+use super::{Catalog, CommandText};
+
+pub const CATALOG: Catalog = Catalog {
+    language_name: "繁體中文",
+    list_separator: "、",
+    commands: &[
+        CommandText {
+            name: "about",
+            args: "",
+            description: "關於",
+            usage: "顯示機器人簡介與原始碼位址",
+        },
+        CommandText {
+            name: "admin",
+            args: "",
+            description: "管理機器人",
+            usage: "",
+        },
+        CommandText {
+            name: "connectfour",
+            args: "",
+            description: "玩四子棋",
+            usage: "發起一局四子棋，由最先點擊棋盤的兩位使用者對戰\n點擊棋盤即可在對應列放置棋子，橫、豎或斜向先連成四子者獲勝",
+        },
+        CommandText {
+            name: "dart",
+            args: "",
+            description: "擲一枚飛鏢",
+            usage: "",
+        },
+        CommandText {
+            name: "dice",
+            args: "",
+            description: "擲一枚骰子",
+            usage: "",
+        },
+        CommandText {
+            name: "help",
+            args: "[命令]",
+            description: "說明資訊",
+            usage: "不帶參數時列出本對話中可用的命令，帶命令名稱時顯示該命令的詳細說明",
+        },
+        CommandText {
+            name: "minesweeper",
+            args: "[棋盤高] [棋盤寬] [地雷數]",
+            description: "玩踩地雷",
+            usage: "不帶參數時使用 8×8 棋盤與 9 個地雷\n棋盤高與棋盤寬均為 1 至 8，地雷數不超過（棋盤高 - 1）×（棋盤寬 - 1）",
+        },
+        CommandText {
+            name: "ocr",
+            args: "",
+            description: "辨識圖片中文字",
+            usage: "選擇目標語言後，回覆機器人的提示訊息傳送圖片，即可辨識圖片中的文字\n僅發起命令的使用者可以選擇語言與傳送圖片",
+        },
+        CommandText {
+            name: "reversi",
+            args: "",
+            description: "玩黑白棋",
+            usage: "發起一局黑白棋，由最先點擊棋盤的兩位使用者對戰\n棋盤填滿或雙方均無處落子時，棋子多者獲勝",
+        },
+        CommandText {
+            name: "settings",
+            args: "",
+            description: "開啟或關閉本對話中的命令與關鍵字（群組管理員）",
+            usage: "傳送設定面板，點擊按鈕開啟或關閉本對話中的命令與關鍵字觸發，或設定本對話使用的語言\n群組中僅管理員可以修改",
+        },
+        CommandText {
+            name: "slot",
+            args: "",
+            description: "轉一次拉霸機",
+            usage: "",
+        },
+        CommandText {
+            name: "start",
+            args: "",
+            description: "開始使用",
+            usage: "",
+        },
+        CommandText {
+            name: "tictactoe",
+            args: "",
+            description: "玩井字棋",
+            usage: "發起一局井字棋，由最先點擊棋盤的兩位使用者對戰\n橫、豎或斜向先連成三子者獲勝",
+        },
+    ],
+
+    rate_limited: "操作過於頻繁，請稍後再試",
+    game_not_found: "找不到遊戲",
+    not_your_turn: "不是你的回合",
+    invalid_move: "無法在此落子",
+    game_timed_out: "遊戲已逾時",
+    player: "{piece}：{name}",
+    winner: "{name} 贏了",
+    draw: "平手",
+    next_player: "輪到：{piece}",
+
+    about: r#"
+eaimty_bot
+
+個人用 Telegram Bot
+
+取得說明資訊 /help
+
+原始碼：https://github.com/EAimTY/eaimty_bot
+"#,
+    start: r#"
+eaimty_bot
+
+個人用 Telegram Bot
+
+取得說明資訊 /help
+"#,
+
+    agree_no: "沒有",
+    agree_pass: "好，沒有，通過！",
+
+    help_hint: "傳送 /help <命令> 查看命令的詳細說明",
+    help_unknown_command: "未知命令：/{name}",
+    help_disabled_command: "/{name} 已在本對話中關閉",
+    help_keywords: "關鍵字：{keywords}",
+
+    settings_prompt: "點擊按鈕開啟或關閉本對話中的命令與關鍵字，或設定本對話使用的語言",
+    settings_admin_only: "僅群組管理員可修改設定",
+    settings_keyword: "關鍵字「{keywords}」",
+    settings_auto_language: "跟隨使用者語言",
+
+    connectfour_title: "四子棋",
+
+    minesweeper_title: "踩地雷",
+    minesweeper_invalid_args: "參數錯誤",
+    minesweeper_steps: "{name}：{steps} 次操作",
+    minesweeper_time: "用時：{minutes} 分 {seconds} 秒",
+    minesweeper_win: "成功排除所有地雷",
+    minesweeper_exploded: "{name} 引爆了地雷",
+
+    ocr_select_language: "請選擇 OCR 目標語言",
+    ocr_send_picture: "目標語言：{lang}，請以需要辨識的圖片回覆此則訊息（以圖片方式傳送）",
+    ocr_reselect: "重新選擇",
+    ocr_not_initiator: "不是命令發起者",
+    ocr_session_not_found: "找不到對話",
+    ocr_timed_out: "OCR 對話已逾時",
+    ocr_download_failed: "圖片取得失敗",
+
+    reversi_title: "黑白棋",
+    reversi_score: "⚫：{black} ⚪：{white}",
+
+    tictactoe_title: "井字棋",
+
+    admin_usage: r#"
+/admin stats - 執行狀態
+/admin sessions <遊戲> - 列出進行中的對話
+/admin kill <對話 ID> <訊息 ID> - 結束對話
+/admin broadcast <內容> - 向廣播對話傳送訊息
+/admin access - 列出存取規則
+/admin allow <chat|user> <ID> - 加入白名單
+/admin deny <chat|user> <ID> - 加入黑名單
+/admin unlist <chat|user> <ID> - 移出白名單或黑名單
+"#,
+    admin_uptime: "執行時間：{days} 天 {hours} 小時 {minutes} 分 {seconds} 秒",
+    admin_sessions: "對話：",
+    admin_updates: "更新：",
+    admin_count: "{name}：{count}",
+    admin_unknown_game: "未知遊戲：{name}",
+    admin_no_sessions: "沒有進行中的對話",
+    admin_session_list: "共 {count} 個對話（對話 ID 訊息 ID 閒置時間）：",
+    admin_session: "{chat_id} {msg_id} {idle} 秒",
+    admin_session_killed: "已結束 {name} 對話",
+    admin_session_not_found: "找不到對話",
+    admin_no_rules: "沒有存取規則",
+    admin_rule_list: "存取規則：",
+    admin_rule_fixed: "（設定檔）",
+    admin_rule_not_editable: "{subject} 的規則由設定檔設定，無法修改",
+    admin_rule_not_persistent: "未設定 storage 或 state_file，存取規則無法在重新啟動後保留，因此無法透過指令修改",
+    admin_rule_set: "已設定 {rule} {subject}",
+    admin_rule_removed: "已移除 {subject} 的規則",
+    admin_no_broadcast_chats: "未設定廣播對話",
+    admin_broadcast_sent: "已傳送至 {count} 個對話",
+    admin_broadcast_failed: "已傳送至 {count} 個對話，傳送失敗：\n{errors}",
+    admin_broadcast_error: "{chat_id}（{error}）",
+};
@@ -0,0 +1,337 @@
+use anyhow::{anyhow, bail, Error, Result};
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+const PRUNE_PERIOD: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub period: Duration,
+}
+
+impl RateLimit {
+    fn get_refill_rate(&self) -> f64 {
+        f64::from(self.burst) / self.period.as_secs_f64()
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (burst, secs) = s
+            .split_once('/')
+            .ok_or_else(|| anyhow!("Invalid rate limit: {s} (expected COUNT/SECS)"))?;
+
+        let burst = burst.parse()?;
+        let secs = secs.parse()?;
+
+        if burst == 0 || secs == 0 {
+            bail!("Invalid rate limit: {s} (count and seconds must be greater than 0)");
+        }
+
+        Ok(Self {
+            burst,
+            period: Duration::from_secs(secs),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RateLimits {
+    pub user: RateLimit,
+    pub chat: RateLimit,
+    pub callback: RateLimit,
+    pub commands: HashMap<String, RateLimit>,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            user: RateLimit {
+                burst: 20,
+                period: Duration::from_secs(60),
+            },
+            chat: RateLimit {
+                burst: 60,
+                period: Duration::from_secs(60),
+            },
+            callback: RateLimit {
+                burst: 30,
+                period: Duration::from_secs(10),
+            },
+            commands: HashMap::new(),
+        }
+    }
+}
+
+pub enum Verdict {
+    Allowed,
+    Limited { scope: &'static str, notify: bool },
+}
+
+pub struct RateLimiter {
+    limits: RateLimits,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self {
+            limits,
+            buckets: Mutex::new(Buckets {
+                map: HashMap::new(),
+                last_prune: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn check(&self, user_id: i64, chat_id: Option<i64>, command: Option<&str>) -> Verdict {
+        let mut keys = vec![(BucketKey::User(user_id), self.limits.user)];
+
+        if let Some(chat_id) = chat_id {
+            keys.push((BucketKey::Chat(chat_id), self.limits.chat));
+        }
+
+        if let Some(command) = command {
+            let command = command.trim_start_matches('/');
+
+            if let Some(limit) = self.limits.commands.get(command) {
+                keys.push((BucketKey::Command(user_id, command.to_owned()), *limit));
+            }
+        }
+
+        self.take(&keys, Instant::now())
+    }
+
+    pub fn check_callback(&self, user_id: i64) -> Verdict {
+        self.take(
+            &[(BucketKey::Callback(user_id), self.limits.callback)],
+            Instant::now(),
+        )
+    }
+
+    fn take(&self, keys: &[(BucketKey, RateLimit)], now: Instant) -> Verdict {
+        let mut buckets = self.buckets.lock();
+
+        if now.duration_since(buckets.last_prune) >= PRUNE_PERIOD {
+            buckets.prune(&self.limits, now);
+        }
+
+        for (key, limit) in keys {
+            let bucket = buckets
+                .map
+                .entry(key.clone())
+                .or_insert_with(|| Bucket::new(*limit, now));
+
+            bucket.refill(*limit, now);
+
+            if bucket.tokens < 1.0 {
+                let notify = !bucket.notified;
+                bucket.notified = true;
+
+                return Verdict::Limited {
+                    scope: key.get_scope(),
+                    notify,
+                };
+            }
+        }
+
+        for (key, _) in keys {
+            if let Some(bucket) = buckets.map.get_mut(key) {
+                bucket.tokens -= 1.0;
+                bucket.notified = false;
+            }
+        }
+
+        Verdict::Allowed
+    }
+}
+
+struct Buckets {
+    map: HashMap<BucketKey, Bucket>,
+    last_prune: Instant,
+}
+
+impl Buckets {
+    fn prune(&mut self, limits: &RateLimits, now: Instant) {
+        self.map.retain(|key, bucket| {
+            let limit = match key {
+                BucketKey::User(_) => Some(limits.user),
+                BucketKey::Chat(_) => Some(limits.chat),
+                BucketKey::Callback(_) => Some(limits.callback),
+                BucketKey::Command(_, command) => limits.commands.get(command).copied(),
+            };
+
+            limit.map_or(false, |limit| {
+                bucket.refill(limit, now);
+                bucket.tokens < f64::from(limit.burst)
+            })
+        });
+
+        self.last_prune = now;
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum BucketKey {
+    User(i64),
+    Chat(i64),
+    Callback(i64),
+    Command(i64, String),
+}
+
+impl BucketKey {
+    fn get_scope(&self) -> &'static str {
+        match self {
+            Self::User(_) => "user",
+            Self::Chat(_) => "chat",
+            Self::Callback(_) => "callback",
+            Self::Command(_, _) => "command",
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    notified: bool,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit.burst),
+            last_refill: now,
+            notified: false,
+        }
+    }
+
+    fn refill(&mut self, limit: RateLimit, now: Instant) {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.get_refill_rate()).min(f64::from(limit.burst));
+        self.last_refill = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(burst: u32, secs: u64) -> RateLimit {
+        RateLimit {
+            burst,
+            period: Duration::from_secs(secs),
+        }
+    }
+
+    fn is_limited(verdict: Verdict, expected_scope: &str) -> bool {
+        matches!(verdict, Verdict::Limited { scope, .. } if scope == expected_scope)
+    }
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let limiter = RateLimiter::new(RateLimits {
+            user: limit(2, 10),
+            ..RateLimits::default()
+        });
+
+        let keys = [(BucketKey::User(1), limiter.limits.user)];
+        let now = Instant::now();
+
+        assert!(matches!(limiter.take(&keys, now), Verdict::Allowed));
+        assert!(matches!(limiter.take(&keys, now), Verdict::Allowed));
+        assert!(matches!(
+            limiter.take(&keys, now),
+            Verdict::Limited { notify: true, .. }
+        ));
+        assert!(matches!(
+            limiter.take(&keys, now),
+            Verdict::Limited { notify: false, .. }
+        ));
+
+        let now = now + Duration::from_secs(5);
+        assert!(matches!(limiter.take(&keys, now), Verdict::Allowed));
+        assert!(matches!(
+            limiter.take(&keys, now),
+            Verdict::Limited { notify: true, .. }
+        ));
+
+        let now = now + Duration::from_secs(60);
+        assert!(matches!(limiter.take(&keys, now), Verdict::Allowed));
+        assert!(matches!(limiter.take(&keys, now), Verdict::Allowed));
+        assert!(is_limited(limiter.take(&keys, now), "user"));
+    }
+
+    #[test]
+    fn chat_bucket_is_shared_by_users() {
+        let limiter = RateLimiter::new(RateLimits {
+            chat: limit(2, 60),
+            ..RateLimits::default()
+        });
+
+        assert!(matches!(limiter.check(1, Some(-1), None), Verdict::Allowed));
+        assert!(matches!(limiter.check(2, Some(-1), None), Verdict::Allowed));
+        assert!(is_limited(limiter.check(3, Some(-1), None), "chat"));
+        assert!(matches!(limiter.check(3, Some(-2), None), Verdict::Allowed));
+        assert!(matches!(limiter.check(3, None, None), Verdict::Allowed));
+    }
+
+    #[test]
+    fn command_limits_apply_per_user_and_command() {
+        let limiter = RateLimiter::new(RateLimits {
+            commands: HashMap::from([(String::from("ocr"), limit(1, 60))]),
+            ..RateLimits::default()
+        });
+
+        assert!(matches!(
+            limiter.check(1, None, Some("/ocr")),
+            Verdict::Allowed
+        ));
+        assert!(is_limited(limiter.check(1, None, Some("ocr")), "command"));
+        assert!(matches!(
+            limiter.check(1, None, Some("/help")),
+            Verdict::Allowed
+        ));
+        assert!(matches!(limiter.check(1, None, None), Verdict::Allowed));
+        assert!(matches!(
+            limiter.check(2, None, Some("/ocr")),
+            Verdict::Allowed
+        ));
+    }
+
+    #[test]
+    fn limited_requests_do_not_consume_other_buckets() {
+        let limiter = RateLimiter::new(RateLimits {
+            user: limit(1, 60),
+            chat: limit(2, 60),
+            ..RateLimits::default()
+        });
+
+        assert!(matches!(limiter.check(1, Some(-1), None), Verdict::Allowed));
+        assert!(is_limited(limiter.check(1, Some(-1), None), "user"));
+        assert!(is_limited(limiter.check(1, Some(-1), None), "user"));
+        assert!(matches!(limiter.check(2, Some(-1), None), Verdict::Allowed));
+        assert!(is_limited(limiter.check(3, Some(-1), None), "chat"));
+    }
+
+    #[test]
+    fn callbacks_use_their_own_bucket() {
+        let limiter = RateLimiter::new(RateLimits {
+            user: limit(1, 60),
+            callback: limit(2, 60),
+            ..RateLimits::default()
+        });
+
+        assert!(matches!(limiter.check(1, Some(-1), None), Verdict::Allowed));
+        assert!(matches!(limiter.check_callback(1), Verdict::Allowed));
+        assert!(matches!(limiter.check_callback(1), Verdict::Allowed));
+        assert!(is_limited(limiter.check_callback(1), "callback"));
+        assert!(matches!(limiter.check_callback(2), Verdict::Allowed));
+    }
+}
@@ -1,14 +1,34 @@
 pub use crate::{
-    config::{Config, ConfigBuilder},
-    database::Database,
+    config::{Config, ConfigBuilder, Feature, LogFormat},
+    database::{Database, SessionLifetimes},
     handler::Handler,
+    limiter::RateLimits,
+    outbox::SendRates,
 };
-use std::{env, process, time::Duration};
+use anyhow::Result;
+use std::{env, process};
+use tokio::signal;
+use tracing::{error, info};
 
+#[cfg(unix)]
+use tokio::signal::unix::SignalKind;
+
+mod access;
+mod api;
 mod bot;
 mod config;
 mod database;
 mod handler;
+mod health;
+mod i18n;
+mod limiter;
+mod metrics;
+mod monitor;
+mod outbox;
+mod webhook;
+
+#[cfg(test)]
+mod tests;
 
 #[tokio::main]
 async fn main() {
@@ -24,14 +44,83 @@ async fn main() {
         }
     };
 
-    let (db, gc) = Database::init(Duration::from_secs(3600), Duration::from_secs(3));
-    tokio::spawn(gc);
+    init_logger(&cfg);
 
-    match bot::run(cfg, db).await {
-        Ok(()) => (),
+    let (db, gc, expired) = match Database::init(&cfg.storage, cfg.session_lifetimes, cfg.gc_period)
+    {
+        Ok(db) => db,
         Err(err) => {
-            eprintln!("{err}");
+            error!(error = %format!("{err:#}"), "Failed to open session storage");
+            process::exit(1);
+        }
+    };
+
+    let state_file = cfg.state_file.clone();
+
+    let saver = if let Some(path) = &state_file {
+        match db.restore(path) {
+            Ok(()) => (),
+            Err(err) => {
+                error!(error = %format!("{err:#}"), "Failed to restore sessions");
+                process::exit(1);
+            }
+        }
+
+        Some(tokio::spawn(
+            db.clone().save_periodically(path.clone(), cfg.save_period),
+        ))
+    } else {
+        None
+    };
+
+    let gc = tokio::spawn(gc);
+
+    let res = bot::run(cfg, db.clone(), expired, shutdown_signal()).await;
+
+    gc.abort();
+
+    if let Some(saver) = saver {
+        saver.abort();
+    }
+
+    if let Some(path) = &state_file {
+        match db.save(path).await {
+            Ok(()) => (),
+            Err(err) => error!(error = %format!("{err:#}"), "Failed to save sessions"),
+        }
+    }
+
+    match res {
+        Ok(()) => info!("Shut down"),
+        Err(err) => {
+            error!(error = %format!("{err:#}"), "Bot stopped");
             process::exit(1);
         }
     }
 }
+
+fn init_logger(cfg: &Config) {
+    let builder = tracing_subscriber::fmt().with_max_level(cfg.log_level);
+
+    match cfg.log_format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.json().init(),
+    }
+}
+
+async fn shutdown_signal() -> Result<()> {
+    #[cfg(unix)]
+    {
+        let mut terminate = signal::unix::signal(SignalKind::terminate())?;
+
+        tokio::select! {
+            res = signal::ctrl_c() => res?,
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    signal::ctrl_c().await?;
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use crate::Database;
+use anyhow::Result;
+use prometheus::{
+    core::Collector, Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec,
+    Opts, Registry, TextEncoder,
+};
+
+pub struct Metrics {
+    registry: Registry,
+    pub updates: IntCounterVec,
+    pub commands: IntCounterVec,
+    pub handler_errors: IntCounterVec,
+    pub rate_limited: IntCounterVec,
+    pub api_requests: HistogramVec,
+    pub ocr_duration: Histogram,
+    sessions: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new_custom(Some(String::from("eaimty_bot")), None)?;
+
+        let updates = IntCounterVec::new(
+            Opts::new("updates_total", "Updates received by kind"),
+            &["kind"],
+        )?;
+
+        let commands = IntCounterVec::new(
+            Opts::new("commands_total", "Commands handled by name"),
+            &["command"],
+        )?;
+
+        let handler_errors = IntCounterVec::new(
+            Opts::new("handler_errors_total", "Errors returned by handlers"),
+            &["handler"],
+        )?;
+
+        let rate_limited = IntCounterVec::new(
+            Opts::new("rate_limited_total", "Updates dropped by rate limits"),
+            &["scope"],
+        )?;
+
+        let api_requests = HistogramVec::new(
+            HistogramOpts::new(
+                "api_request_duration_seconds",
+                "Telegram Bot API request latency",
+            ),
+            &["method", "result"],
+        )?;
+
+        let ocr_duration = Histogram::with_opts(
+            HistogramOpts::new("ocr_duration_seconds", "OCR job duration")
+                .buckets(vec![0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]),
+        )?;
+
+        let sessions = IntGaugeVec::new(Opts::new("sessions", "Live sessions by pool"), &["pool"])?;
+
+        registry.register(Box::new(updates.clone()))?;
+        registry.register(Box::new(commands.clone()))?;
+        registry.register(Box::new(handler_errors.clone()))?;
+        registry.register(Box::new(rate_limited.clone()))?;
+        registry.register(Box::new(api_requests.clone()))?;
+        registry.register(Box::new(ocr_duration.clone()))?;
+        registry.register(Box::new(sessions.clone()))?;
+
+        Ok(Self {
+            registry,
+            updates,
+            commands,
+            handler_errors,
+            rate_limited,
+            api_requests,
+            ocr_duration,
+            sessions,
+        })
+    }
+
+    pub fn count_updates(&self) -> Vec<(String, u64)> {
+        self.updates
+            .collect()
+            .iter()
+            .flat_map(|family| family.get_metric())
+            .map(|metric| {
+                let kind = metric
+                    .get_label()
+                    .iter()
+                    .map(|label| label.get_value())
+                    .collect::<String>();
+
+                (kind, metric.get_counter().get_value() as u64)
+            })
+            .collect()
+    }
+
+    pub fn encode(&self, database: &Database) -> Result<Vec<u8>> {
+        for (pool, count) in database.count_sessions()? {
+            self.sessions.with_label_values(&[pool]).set(count as i64);
+        }
+
+        let mut buf = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buf)?;
+
+        Ok(buf)
+    }
+}
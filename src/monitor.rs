@@ -0,0 +1,98 @@
+use crate::{health::Health, metrics::Metrics, Database};
+use anyhow::{anyhow, Result};
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use prometheus::TEXT_FORMAT;
+use std::{convert::Infallible, future::Future, net::SocketAddr, sync::Arc};
+use tracing::error;
+
+#[derive(Clone)]
+pub struct Monitor {
+    pub metrics: Arc<Metrics>,
+    pub health: Arc<Health>,
+    pub database: Arc<Database>,
+}
+
+impl Monitor {
+    pub fn serve(self, port: u16) -> Result<impl Future<Output = ()>> {
+        let addr = SocketAddr::from(([0, 0, 0, 0], port));
+
+        let builder = Server::try_bind(&addr)
+            .map_err(|err| anyhow!("Failed to listen on {addr} for monitoring: {err}"))?;
+
+        let make_svc = make_service_fn(move |_| {
+            let monitor = self.clone();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let res = monitor.handle_request(req);
+                    async move { Ok::<_, Infallible>(res) }
+                }))
+            }
+        });
+
+        let server = builder.serve(make_svc);
+
+        Ok(async move {
+            if let Err(err) = server.await {
+                error!(error = %format!("{err:#}"), "Monitoring server stopped");
+            }
+        })
+    }
+
+    fn handle_request(&self, req: Request<Body>) -> Response<Body> {
+        if req.method() != Method::GET {
+            return status(StatusCode::METHOD_NOT_ALLOWED);
+        }
+
+        match req.uri().path() {
+            "/metrics" => self.handle_metrics(),
+            "/healthz" => self.handle_healthz(),
+            _ => status(StatusCode::NOT_FOUND),
+        }
+    }
+
+    fn handle_metrics(&self) -> Response<Body> {
+        match self.metrics.encode(&self.database) {
+            Ok(buf) => Response::builder()
+                .header(CONTENT_TYPE, TEXT_FORMAT)
+                .body(Body::from(buf))
+                .unwrap_or_else(|_| status(StatusCode::INTERNAL_SERVER_ERROR)),
+            Err(err) => {
+                error!(error = %format!("{err:#}"), "Failed to encode metrics");
+                status(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+
+    fn handle_healthz(&self) -> Response<Body> {
+        let report = self.health.report(&self.database);
+
+        let code = if report.ok {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+
+        match serde_json::to_vec(&report) {
+            Ok(buf) => Response::builder()
+                .status(code)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(buf))
+                .unwrap_or_else(|_| status(StatusCode::INTERNAL_SERVER_ERROR)),
+            Err(err) => {
+                error!(error = %err, "Failed to encode health report");
+                status(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
+
+fn status(status: StatusCode) -> Response<Body> {
+    let mut res = Response::new(Body::empty());
+    *res.status_mut() = status;
+    res
+}
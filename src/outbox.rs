@@ -0,0 +1,245 @@
+use crate::api::{Api, ApiMethod};
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+use tgbot::{methods::EditMessageText, ExecuteError};
+use tokio::{
+    sync::{oneshot, Notify},
+    time::{self, Instant},
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct SendRates {
+    pub global: u32,
+    pub chat: u32,
+    pub group: u32,
+}
+
+impl Default for SendRates {
+    fn default() -> Self {
+        Self {
+            global: 30,
+            chat: 1,
+            group: 20,
+        }
+    }
+}
+
+pub struct Outbox {
+    api: Arc<Api>,
+    queue: Arc<Mutex<Queue>>,
+    notify: Arc<Notify>,
+}
+
+impl Outbox {
+    pub fn new(api: Arc<Api>, rates: SendRates) -> (Arc<Self>, impl Future<Output = ()>) {
+        let queue = Arc::new(Mutex::new(Queue {
+            chats: HashMap::new(),
+            next_send: Instant::now(),
+            global_interval: Duration::from_secs(1) / rates.global,
+            chat_interval: Duration::from_secs(1) / rates.chat,
+            group_interval: Duration::from_secs(60) / rates.group,
+        }));
+
+        let notify = Arc::new(Notify::new());
+
+        let outbox = Arc::new(Self {
+            api,
+            queue: queue.clone(),
+            notify: notify.clone(),
+        });
+
+        (outbox, dispatch(queue, notify))
+    }
+
+    pub async fn send<M>(&self, chat_id: i64, method: M) -> Result<M::Response, ExecuteError>
+    where
+        M: ApiMethod,
+        M::Response: DeserializeOwned,
+    {
+        let _permit = self.wait_turn(chat_id, None).await.into_permit();
+        self.api.execute(method).await
+    }
+
+    pub async fn edit(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+        method: EditMessageText,
+    ) -> Result<(), ExecuteError> {
+        let turn = self.wait_turn(chat_id, Some(message_id)).await;
+
+        if let Turn::Superseded = turn {
+            return Ok(());
+        }
+
+        let _permit = turn.into_permit();
+        self.api.execute(method).await?;
+
+        Ok(())
+    }
+
+    async fn wait_turn(&self, chat_id: i64, edit: Option<i64>) -> Turn {
+        let (turn_tx, turn_rx) = oneshot::channel();
+
+        {
+            let mut queue = self.queue.lock();
+            let chat = queue.chats.entry(chat_id).or_insert_with(Chat::new);
+
+            if edit.is_some() && matches!(chat.jobs.back(), Some(job) if job.edit == edit) {
+                if let Some(job) = chat.jobs.pop_back() {
+                    let _ = job.turn.send(Turn::Superseded);
+                }
+            }
+
+            chat.jobs.push_back(Job {
+                edit,
+                turn: turn_tx,
+            });
+        }
+
+        self.notify.notify_one();
+
+        turn_rx.await.unwrap_or(Turn::Unscheduled)
+    }
+}
+
+struct Queue {
+    chats: HashMap<i64, Chat>,
+    next_send: Instant,
+    global_interval: Duration,
+    chat_interval: Duration,
+    group_interval: Duration,
+}
+
+impl Queue {
+    fn get_interval(&self, chat_id: i64) -> Duration {
+        if chat_id < 0 {
+            self.group_interval
+        } else {
+            self.chat_interval
+        }
+    }
+}
+
+struct Chat {
+    jobs: VecDeque<Job>,
+    busy: bool,
+    next_send: Instant,
+}
+
+impl Chat {
+    fn new() -> Self {
+        Self {
+            jobs: VecDeque::new(),
+            busy: false,
+            next_send: Instant::now(),
+        }
+    }
+
+    fn get_ready_time(&self, now: Instant) -> Instant {
+        match self.jobs.front() {
+            Some(job) if job.edit.is_some() => now,
+            _ => self.next_send,
+        }
+    }
+}
+
+struct Job {
+    edit: Option<i64>,
+    turn: oneshot::Sender<Turn>,
+}
+
+enum Turn {
+    Granted(Permit),
+    Superseded,
+    Unscheduled,
+}
+
+impl Turn {
+    fn into_permit(self) -> Option<Permit> {
+        match self {
+            Self::Granted(permit) => Some(permit),
+            Self::Superseded | Self::Unscheduled => None,
+        }
+    }
+}
+
+struct Permit {
+    queue: Arc<Mutex<Queue>>,
+    notify: Arc<Notify>,
+    chat_id: i64,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(chat) = self.queue.lock().chats.get_mut(&self.chat_id) {
+            chat.busy = false;
+        }
+
+        self.notify.notify_one();
+    }
+}
+
+async fn dispatch(queue: Arc<Mutex<Queue>>, notify: Arc<Notify>) {
+    loop {
+        if let Some(wake) = dispatch_next(&queue, &notify) {
+            tokio::select! {
+                () = time::sleep_until(wake) => {}
+                () = notify.notified() => {}
+            }
+        } else {
+            notify.notified().await;
+        }
+    }
+}
+
+fn dispatch_next(queue: &Arc<Mutex<Queue>>, notify: &Arc<Notify>) -> Option<Instant> {
+    let mut guard = queue.lock();
+    let now = Instant::now();
+
+    guard
+        .chats
+        .retain(|_, chat| chat.busy || !chat.jobs.is_empty() || chat.next_send > now);
+
+    let (chat_id, ready) = guard
+        .chats
+        .iter()
+        .filter(|(_, chat)| !chat.busy && !chat.jobs.is_empty())
+        .map(|(chat_id, chat)| (*chat_id, chat.get_ready_time(now)))
+        .min_by_key(|(_, ready)| *ready)
+        .map(|(chat_id, ready)| (chat_id, ready.max(guard.next_send)))?;
+
+    if ready > now {
+        return Some(ready);
+    }
+
+    let interval = guard.get_interval(chat_id);
+    guard.next_send = now + guard.global_interval;
+
+    let chat = guard.chats.get_mut(&chat_id)?;
+    let job = chat.jobs.pop_front()?;
+
+    chat.busy = true;
+
+    if job.edit.is_none() {
+        chat.next_send = now + interval;
+    }
+
+    drop(guard);
+
+    let permit = Permit {
+        queue: queue.clone(),
+        notify: notify.clone(),
+        chat_id,
+    };
+
+    let _ = job.turn.send(Turn::Granted(permit));
+
+    Some(now)
+}
@@ -0,0 +1,573 @@
+use hyper::{
+    body,
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    sync::{oneshot, watch},
+    time,
+};
+
+pub const TOKEN: &str = "123456:TEST";
+pub const BOT_USERNAME: &str = "test_bot";
+pub const EN_USER: i64 = 3;
+pub const FR_USER: i64 = 4;
+
+const BOT_ID: i64 = 123456;
+const WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub method: String,
+    pub params: Value,
+    pub result: Value,
+}
+
+pub struct MockApi {
+    addr: SocketAddr,
+    state: Arc<State>,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl MockApi {
+    pub fn start() -> Self {
+        let state = Arc::new(State::new());
+
+        let make_service = {
+            let state = state.clone();
+
+            make_service_fn(move |_| {
+                let state = state.clone();
+                async move { Ok::<_, Infallible>(service_fn(move |req| handle(state.clone(), req))) }
+            })
+        };
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let addr = server.local_addr();
+
+        tokio::spawn(server.with_graceful_shutdown(async {
+            let _ = shutdown_rx.await;
+        }));
+
+        Self {
+            addr,
+            state,
+            _shutdown: shutdown_tx,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn send_message(&self, chat_id: i64, user_id: i64, text: &str) -> i64 {
+        let update = self.create_message_update(chat_id, user_id, text);
+        let msg_id = update["message"]["message_id"].as_i64().unwrap_or_default();
+
+        self.push_update(update);
+
+        msg_id
+    }
+
+    pub fn create_message_update(&self, chat_id: i64, user_id: i64, text: &str) -> Value {
+        let mut inner = self.state.inner.lock();
+
+        let mut msg = inner.new_message(chat_id, get_user(user_id), None);
+        msg["text"] = json!(text);
+
+        if text.starts_with('/') {
+            let len = text.split_whitespace().next().unwrap_or_default().len();
+            msg["entities"] = json!([{"type": "bot_command", "offset": 0, "length": len}]);
+        }
+
+        inner.store_message(chat_id, msg.clone());
+        inner.new_update("message", msg)
+    }
+
+    pub fn click(&self, chat_id: i64, msg_id: i64, user_id: i64, data: &str) {
+        let mut inner = self.state.inner.lock();
+
+        let msg = inner
+            .messages
+            .get(&(chat_id, msg_id))
+            .cloned()
+            .unwrap_or_else(|| panic!("message {msg_id} not found in chat {chat_id}"));
+
+        let callback_query = json!({
+            "id": (inner.next_update_id + 1).to_string(),
+            "from": get_user(user_id),
+            "message": msg,
+            "chat_instance": chat_id.to_string(),
+            "data": data,
+        });
+
+        let update = inner.new_update("callback_query", callback_query);
+
+        drop(inner);
+        self.push_update(update);
+    }
+
+    pub fn add_bot_to_chat(&self, chat_id: i64, user_id: i64) {
+        let mut inner = self.state.inner.lock();
+
+        let bot_status = json!({
+            "chat": get_chat(chat_id),
+            "from": get_user(user_id),
+            "date": 0,
+            "old_chat_member": {"status": "left", "user": get_me()},
+            "new_chat_member": {"status": "member", "user": get_me()},
+        });
+
+        let update = inner.new_update("my_chat_member", bot_status);
+
+        drop(inner);
+        self.push_update(update);
+    }
+
+    fn push_update(&self, update: Value) {
+        self.state.inner.lock().updates.push(update);
+        self.state.notify();
+    }
+
+    pub fn fail_next(&self, method: &str, status: StatusCode, description: &str) {
+        self.state
+            .inner
+            .lock()
+            .failures
+            .entry(method.to_owned())
+            .or_default()
+            .push_back(Failure::new(status, description));
+    }
+
+    pub fn rate_limit_next(&self, method: &str, retry_after: u64) {
+        let failure = Failure {
+            retry_after: Some(retry_after),
+            ..Failure::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                &format!("Too Many Requests: retry after {retry_after}"),
+            )
+        };
+
+        self.state
+            .inner
+            .lock()
+            .failures
+            .entry(method.to_owned())
+            .or_default()
+            .push_back(failure);
+    }
+
+    pub async fn wait_for_failures(&self, method: &str) {
+        let wait = self.state.wait_until(|inner| {
+            inner
+                .failures
+                .get(method)
+                .map_or(true, VecDeque::is_empty)
+                .then(|| ())
+        });
+
+        time::timeout(WAIT_TIMEOUT, wait)
+            .await
+            .unwrap_or_else(|_| panic!("timed out waiting for `{method}` failures"))
+    }
+
+    pub fn get_message_text(&self, chat_id: i64, msg_id: i64) -> Option<String> {
+        let inner = self.state.inner.lock();
+        let msg = inner.messages.get(&(chat_id, msg_id))?;
+
+        msg["text"].as_str().map(ToOwned::to_owned)
+    }
+
+    pub fn get_calls(&self, method: &str) -> Vec<Call> {
+        self.state
+            .inner
+            .lock()
+            .calls
+            .iter()
+            .filter(|call| call.method == method)
+            .cloned()
+            .collect()
+    }
+
+    pub async fn wait_for_calls(&self, method: &str, count: usize) -> Vec<Call> {
+        let wait = self.state.wait_until(|inner| {
+            let calls = inner
+                .calls
+                .iter()
+                .filter(|call| call.method == method)
+                .cloned()
+                .collect::<Vec<_>>();
+
+            if calls.len() >= count {
+                Some(calls)
+            } else {
+                None
+            }
+        });
+
+        time::timeout(WAIT_TIMEOUT, wait)
+            .await
+            .unwrap_or_else(|_| panic!("timed out waiting for {count} `{method}` calls"))
+    }
+
+    pub async fn wait_for_call(&self, method: &str, pred: impl Fn(&Call) -> bool) -> Call {
+        let wait = self.state.wait_until(|inner| {
+            inner
+                .calls
+                .iter()
+                .find(|call| call.method == method && pred(call))
+                .cloned()
+        });
+
+        time::timeout(WAIT_TIMEOUT, wait)
+            .await
+            .unwrap_or_else(|_| panic!("timed out waiting for `{method}` call"))
+    }
+}
+
+struct State {
+    inner: Mutex<Inner>,
+    changed: watch::Sender<()>,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            inner: Mutex::new(Inner::default()),
+            changed: watch::channel(()).0,
+        }
+    }
+
+    fn notify(&self) {
+        let _ = self.changed.send(());
+    }
+
+    async fn wait_until<T>(&self, mut f: impl FnMut(&mut Inner) -> Option<T>) -> T {
+        let mut changed = self.changed.subscribe();
+
+        loop {
+            if let Some(res) = f(&mut self.inner.lock()) {
+                return res;
+            }
+
+            let _ = changed.changed().await;
+        }
+    }
+
+    async fn execute(&self, method: &str, params: Value) -> Result<Value, Failure> {
+        if method == "getUpdates" {
+            self.inner.lock().calls.push(Call {
+                method: method.to_owned(),
+                params: params.clone(),
+                result: Value::Null,
+            });
+
+            return Ok(self.get_updates(&params).await);
+        }
+
+        let mut inner = self.inner.lock();
+
+        if let Some(err) = inner
+            .failures
+            .get_mut(method)
+            .and_then(|failures| failures.pop_front())
+        {
+            drop(inner);
+            self.notify();
+
+            return Err(err);
+        }
+
+        let result = match method {
+            "getMe" => get_me(),
+            "sendMessage" => {
+                let chat_id = params["chat_id"].as_i64().unwrap_or_default();
+                let reply_to = params["reply_to_message_id"].as_i64();
+
+                let mut msg = inner.new_message(chat_id, get_me(), reply_to);
+                msg["text"] = params["text"].clone();
+                inner.store_message(chat_id, msg.clone());
+
+                msg
+            }
+            "sendDice" => {
+                let chat_id = params["chat_id"].as_i64().unwrap_or_default();
+                let reply_to = params["reply_to_message_id"].as_i64();
+                let emoji = params["emoji"].as_str().unwrap_or("🎲");
+
+                let mut msg = inner.new_message(chat_id, get_me(), reply_to);
+                msg["dice"] = json!({"emoji": emoji, "value": 1});
+                inner.store_message(chat_id, msg.clone());
+
+                msg
+            }
+            "editMessageText" => {
+                let chat_id = params["chat_id"].as_i64().unwrap_or_default();
+                let msg_id = params["message_id"].as_i64().unwrap_or_default();
+
+                let msg = inner.messages.get_mut(&(chat_id, msg_id)).ok_or_else(|| {
+                    Failure::new(
+                        StatusCode::BAD_REQUEST,
+                        "Bad Request: message to edit not found",
+                    )
+                })?;
+
+                msg["text"] = params["text"].clone();
+                msg.clone()
+            }
+            "answerCallbackQuery"
+            | "deleteWebhook"
+            | "leaveChat"
+            | "setMyCommands"
+            | "setWebhook" => json!(true),
+            _ => return Err(Failure::new(StatusCode::NOT_FOUND, "Not Found")),
+        };
+
+        inner.calls.push(Call {
+            method: method.to_owned(),
+            params,
+            result: result.clone(),
+        });
+
+        drop(inner);
+        self.notify();
+
+        Ok(result)
+    }
+
+    async fn get_updates(&self, params: &Value) -> Value {
+        let offset = params["offset"].as_i64().unwrap_or_default();
+        let timeout = Duration::from_secs(params["timeout"].as_u64().unwrap_or_default());
+
+        if let Some(allowed_updates) = params["allowed_updates"].as_array() {
+            self.inner.lock().allowed_updates = allowed_updates
+                .iter()
+                .filter_map(|kind| kind.as_str().map(ToOwned::to_owned))
+                .collect();
+        }
+
+        let wait = self.wait_until(|inner| {
+            let allowed_updates = &inner.allowed_updates;
+
+            inner.updates.retain(|update| {
+                update["update_id"].as_i64() >= Some(offset)
+                    && is_update_allowed(update, allowed_updates)
+            });
+
+            if inner.updates.is_empty() {
+                None
+            } else {
+                Some(inner.updates.clone())
+            }
+        });
+
+        let updates = time::timeout(timeout, wait).await.unwrap_or_default();
+
+        Value::Array(updates)
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    next_update_id: i64,
+    next_message_id: i64,
+    updates: Vec<Value>,
+    messages: HashMap<(i64, i64), Value>,
+    calls: Vec<Call>,
+    failures: HashMap<String, VecDeque<Failure>>,
+    allowed_updates: Vec<String>,
+}
+
+impl Inner {
+    fn new_message(&mut self, chat_id: i64, from: Value, reply_to: Option<i64>) -> Value {
+        self.next_message_id += 1;
+
+        let mut msg = json!({
+            "message_id": self.next_message_id,
+            "date": 0,
+            "from": from,
+            "chat": get_chat(chat_id),
+        });
+
+        if let Some(reply_to) = reply_to.and_then(|id| self.messages.get(&(chat_id, id))) {
+            let mut reply_to = reply_to.clone();
+
+            if let Some(reply_to) = reply_to.as_object_mut() {
+                reply_to.remove("reply_to_message");
+            }
+
+            msg["reply_to_message"] = reply_to;
+        }
+
+        msg
+    }
+
+    fn store_message(&mut self, chat_id: i64, msg: Value) -> i64 {
+        let msg_id = msg["message_id"].as_i64().unwrap_or_default();
+        self.messages.insert((chat_id, msg_id), msg);
+        msg_id
+    }
+
+    fn new_update(&mut self, kind: &str, value: Value) -> Value {
+        self.next_update_id += 1;
+
+        let mut update = json!({ "update_id": self.next_update_id });
+        update[kind] = value;
+
+        update
+    }
+}
+
+struct Failure {
+    status: StatusCode,
+    description: String,
+    retry_after: Option<u64>,
+}
+
+impl Failure {
+    fn new(status: StatusCode, description: &str) -> Self {
+        Self {
+            status,
+            description: description.to_owned(),
+            retry_after: None,
+        }
+    }
+
+    fn into_response(self) -> Response<Body> {
+        let mut body = json!({
+            "ok": false,
+            "error_code": self.status.as_u16(),
+            "description": self.description,
+        });
+
+        if let Some(retry_after) = self.retry_after {
+            body["parameters"] = json!({ "retry_after": retry_after });
+        }
+
+        let mut res = Response::new(Body::from(body.to_string()));
+        *res.status_mut() = self.status;
+        res
+    }
+}
+
+async fn handle(state: Arc<State>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let path = req.uri().path().to_owned();
+
+    let method = match path
+        .strip_prefix("/bot")
+        .and_then(|path| path.split_once('/'))
+    {
+        Some((token, method)) if token == TOKEN => method.to_owned(),
+        Some(_) => return Ok(respond_error(StatusCode::UNAUTHORIZED, "Unauthorized")),
+        None => return Ok(respond_error(StatusCode::NOT_FOUND, "Not Found")),
+    };
+
+    let boundary = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("multipart/form-data; boundary="))
+        .map(ToOwned::to_owned);
+
+    let params = match body::to_bytes(req.into_body()).await {
+        Ok(body) if body.is_empty() => json!({}),
+        Ok(body) => match boundary {
+            Some(boundary) => parse_multipart(&String::from_utf8_lossy(&body), &boundary),
+            None => serde_json::from_slice(&body).unwrap_or_else(|_| json!({})),
+        },
+        Err(err) => return Ok(respond_error(StatusCode::BAD_REQUEST, &err.to_string())),
+    };
+
+    match state.execute(&method, params).await {
+        Ok(result) => Ok(Response::new(Body::from(
+            json!({"ok": true, "result": result}).to_string(),
+        ))),
+        Err(failure) => Ok(failure.into_response()),
+    }
+}
+
+fn parse_multipart(body: &str, boundary: &str) -> Value {
+    let mut params = json!({});
+
+    for part in body.split(&format!("--{boundary}")) {
+        let (headers, value) = match part.split_once("\r\n\r\n") {
+            Some(part) => part,
+            None => continue,
+        };
+
+        let name = headers
+            .split("name=\"")
+            .nth(1)
+            .and_then(|name| name.split('"').next());
+
+        if let Some(name) = name {
+            params[name] = json!(value.trim_end_matches("\r\n"));
+        }
+    }
+
+    params
+}
+
+fn is_update_allowed(update: &Value, allowed_updates: &[String]) -> bool {
+    let kind = update
+        .as_object()
+        .and_then(|update| update.keys().find(|key| *key != "update_id"));
+
+    match kind {
+        Some(kind) if allowed_updates.is_empty() => kind != "chat_member",
+        Some(kind) => allowed_updates.contains(kind),
+        None => false,
+    }
+}
+
+fn respond_error(status: StatusCode, description: &str) -> Response<Body> {
+    Failure::new(status, description).into_response()
+}
+
+fn get_me() -> Value {
+    json!({
+        "id": BOT_ID,
+        "is_bot": true,
+        "first_name": "Test",
+        "username": BOT_USERNAME,
+        "can_join_groups": true,
+        "can_read_all_group_messages": false,
+        "supports_inline_queries": false,
+    })
+}
+
+fn get_user(user_id: i64) -> Value {
+    let mut user = json!({
+        "id": user_id,
+        "is_bot": false,
+        "first_name": format!("user{user_id}"),
+    });
+
+    match user_id {
+        EN_USER => user["language_code"] = json!("en"),
+        FR_USER => user["language_code"] = json!("fr"),
+        _ => {}
+    }
+
+    user
+}
+
+fn get_chat(chat_id: i64) -> Value {
+    if chat_id > 0 {
+        json!({"id": chat_id, "type": "private", "first_name": format!("user{chat_id}")})
+    } else {
+        json!({"id": chat_id, "type": "supergroup", "title": "Test"})
+    }
+}
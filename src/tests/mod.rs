@@ -0,0 +1,803 @@
+use self::mock::{MockApi, BOT_USERNAME, EN_USER, FR_USER, TOKEN};
+use crate::{bot, ConfigBuilder, Database, Handler};
+use anyhow::Result;
+use reqwest::{header::CONTENT_TYPE, Client, StatusCode};
+use serde_json::Value;
+use std::{
+    env, fs,
+    net::TcpListener as StdTcpListener,
+    process,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::oneshot,
+    task::{self, JoinHandle},
+    time,
+};
+
+mod mock;
+
+const GROUP: i64 = -100;
+const ALICE: i64 = 1;
+const BOB: i64 = 2;
+
+const SEND_RATES: [(&str, &str); 2] =
+    [("--chat-send-rate", "1000"), ("--group-send-rate", "60000")];
+
+const SECRET_TOKEN_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+
+struct TestBot {
+    database: Arc<Database>,
+    shutdown: oneshot::Sender<()>,
+    handle: JoinHandle<Result<()>>,
+}
+
+impl TestBot {
+    async fn start(mock: &MockApi) -> Self {
+        Self::start_with(mock, &[]).await
+    }
+
+    async fn start_with(mock: &MockApi, extra_args: &[&str]) -> Self {
+        let url = mock.url();
+
+        let mut args = vec!["eaimty_bot", "--token", TOKEN, "--api-url", &url];
+
+        for (name, rate) in SEND_RATES {
+            if !extra_args.contains(&name) {
+                args.extend([name, rate]);
+            }
+        }
+
+        let args = args
+            .iter()
+            .chain(extra_args)
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>();
+
+        let config = ConfigBuilder::new().parse(&args).unwrap();
+
+        let (database, gc, expired) =
+            Database::init(&config.storage, config.session_lifetimes, config.gc_period).unwrap();
+        task::spawn(gc);
+
+        let (shutdown, shutdown_rx) = oneshot::channel();
+
+        let handle = task::spawn(bot::run(config, database.clone(), expired, async {
+            let _ = shutdown_rx.await;
+            Ok(())
+        }));
+
+        mock.wait_for_calls("getMe", 1).await;
+
+        Self {
+            database,
+            shutdown,
+            handle,
+        }
+    }
+
+    async fn stop(self) {
+        let _ = self.shutdown.send(());
+        self.handle.await.unwrap().unwrap();
+    }
+}
+
+fn get_free_port() -> u16 {
+    StdTcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+async fn post_update(
+    port: u16,
+    path: &str,
+    secret_token: Option<&str>,
+    update: &Value,
+) -> StatusCode {
+    let client = Client::new();
+
+    for _ in 0..50 {
+        let mut req = client
+            .post(format!("http://127.0.0.1:{port}{path}"))
+            .header(CONTENT_TYPE, "application/json")
+            .body(update.to_string());
+
+        if let Some(secret_token) = secret_token {
+            req = req.header(SECRET_TOKEN_HEADER, secret_token);
+        }
+
+        let res = req.send().await;
+
+        match res {
+            Ok(res) => return res.status(),
+            Err(err) if err.is_connect() => time::sleep(Duration::from_millis(20)).await,
+            Err(err) => panic!("failed to post update: {err}"),
+        }
+    }
+
+    panic!("webhook server is not listening on port {port}");
+}
+
+async fn start_tictactoe(mock: &MockApi, user_id: i64) -> i64 {
+    let cmd_msg_id = mock.send_message(GROUP, user_id, &format!("/tictactoe@{BOT_USERNAME}"));
+
+    let board = mock
+        .wait_for_call("sendMessage", |call| {
+            call.result["reply_to_message"]["message_id"].as_i64() == Some(cmd_msg_id)
+        })
+        .await;
+
+    board.result["message_id"].as_i64().unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn tictactoe_until_win() {
+    let mock = MockApi::start();
+    let bot = TestBot::start(&mock).await;
+
+    let board_id = start_tictactoe(&mock, ALICE).await;
+
+    let moves = [
+        (ALICE, 0, 0),
+        (BOB, 1, 0),
+        (ALICE, 0, 1),
+        (BOB, 1, 1),
+        (ALICE, 0, 2),
+    ];
+
+    for (i, (user_id, row, col)) in moves.into_iter().enumerate() {
+        mock.click(GROUP, board_id, user_id, &format!("tictactoe-{row}-{col}"));
+
+        let answers = mock.wait_for_calls("answerCallbackQuery", i + 1).await;
+        assert_eq!(answers[i].params.get("text"), None);
+    }
+
+    mock.wait_for_call("editMessageText", |call| {
+        call.params["text"]
+            .as_str()
+            .map_or(false, |text| text.contains("user1 赢了"))
+    })
+    .await;
+
+    mock.click(GROUP, board_id, BOB, "tictactoe-2-2");
+
+    let answers = mock
+        .wait_for_calls("answerCallbackQuery", moves.len() + 1)
+        .await;
+    assert_eq!(answers[moves.len()].params["text"], "找不到游戏");
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn board_keeps_creator_language() {
+    let mock = MockApi::start();
+    let bot = TestBot::start(&mock).await;
+
+    let board_id = start_tictactoe(&mock, EN_USER).await;
+
+    mock.click(GROUP, board_id, ALICE, "tictactoe-0-0");
+    let edit = mock.wait_for_calls("editMessageText", 1).await;
+    assert!(edit[0].params["text"]
+        .as_str()
+        .unwrap()
+        .contains("Next: ⭕"));
+
+    mock.click(GROUP, board_id, BOB, "tictactoe-0-0");
+    let answers = mock.wait_for_calls("answerCallbackQuery", 2).await;
+    assert_eq!(answers[1].params["text"], "不是你的回合");
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn unsupported_language_uses_default_language() {
+    let mock = MockApi::start();
+    let bot = TestBot::start_with(&mock, &["--default-language", "en"]).await;
+
+    let msg_id = mock.send_message(FR_USER, FR_USER, "/help");
+    let help = mock
+        .wait_for_call("sendMessage", |call| {
+            call.params["reply_to_message_id"].as_i64() == Some(msg_id)
+        })
+        .await;
+    assert!(help.params["text"]
+        .as_str()
+        .unwrap()
+        .ends_with("Send /help <command> for detailed help"));
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn tictactoe_rejects_wrong_player() {
+    let mock = MockApi::start();
+    let bot = TestBot::start(&mock).await;
+
+    let board_id = start_tictactoe(&mock, ALICE).await;
+
+    mock.click(GROUP, board_id, ALICE, "tictactoe-0-0");
+    mock.wait_for_calls("answerCallbackQuery", 1).await;
+
+    mock.click(GROUP, board_id, BOB, "tictactoe-0-0");
+    let answers = mock.wait_for_calls("answerCallbackQuery", 2).await;
+    assert_eq!(answers[1].params["text"], "不是你的回合");
+    assert_eq!(answers[1].params["show_alert"], true);
+
+    mock.click(GROUP, board_id, BOB, "tictactoe-1-1");
+    let answers = mock.wait_for_calls("answerCallbackQuery", 3).await;
+    assert_eq!(answers[2].params.get("text"), None);
+
+    mock.click(GROUP, board_id, BOB, "tictactoe-2-2");
+    let answers = mock.wait_for_calls("answerCallbackQuery", 4).await;
+    assert_eq!(answers[3].params["text"], "不是你的回合");
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn callback_rate_limit_answers_clicks() {
+    let mock = MockApi::start();
+    let bot = TestBot::start_with(
+        &mock,
+        &["--user-rate-limit", "1/60", "--callback-rate-limit", "1/60"],
+    )
+    .await;
+
+    let board_id = start_tictactoe(&mock, ALICE).await;
+
+    mock.click(GROUP, board_id, ALICE, "tictactoe-0-0");
+    let answers = mock.wait_for_calls("answerCallbackQuery", 1).await;
+    assert_eq!(answers[0].params.get("text"), None);
+
+    mock.click(GROUP, board_id, BOB, "tictactoe-1-0");
+    let answers = mock.wait_for_calls("answerCallbackQuery", 2).await;
+    assert_eq!(answers[1].params.get("text"), None);
+
+    mock.click(GROUP, board_id, ALICE, "tictactoe-0-1");
+    let answers = mock.wait_for_calls("answerCallbackQuery", 3).await;
+    assert_eq!(answers[2].params["text"], "操作过于频繁，请稍后再试");
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn group_edits_skip_send_rate_limit() {
+    let mock = MockApi::start();
+    let bot = TestBot::start_with(&mock, &["--group-send-rate", "1"]).await;
+
+    let board_id = start_tictactoe(&mock, ALICE).await;
+
+    mock.click(GROUP, board_id, ALICE, "tictactoe-0-0");
+    mock.click(GROUP, board_id, BOB, "tictactoe-1-1");
+
+    mock.wait_for_call("editMessageText", |call| {
+        call.params["text"]
+            .as_str()
+            .map_or(false, |text| text.contains("user2"))
+    })
+    .await;
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn admin_kill_closes_tictactoe_board() {
+    let mock = MockApi::start();
+    let bot = TestBot::start_with(&mock, &["--owner", &ALICE.to_string()]).await;
+
+    let board_id = start_tictactoe(&mock, ALICE).await;
+    let board = mock
+        .wait_for_call("sendMessage", |call| {
+            call.result["message_id"].as_i64() == Some(board_id)
+        })
+        .await;
+    let cmd_msg_id = board.result["reply_to_message"]["message_id"]
+        .as_i64()
+        .unwrap();
+
+    for _ in 0..50 {
+        let session = bot.database.tictactoe.lock().get(&[GROUP, cmd_msg_id]);
+
+        if session
+            .unwrap()
+            .map_or(false, |session| session.message.is_some())
+        {
+            break;
+        }
+
+        time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let msg_id = mock.send_message(ALICE, ALICE, &format!("/admin kill {GROUP} {cmd_msg_id}"));
+
+    let reply = mock
+        .wait_for_call("sendMessage", |call| {
+            call.params["reply_to_message_id"].as_i64() == Some(msg_id)
+        })
+        .await;
+    assert_eq!(reply.params["text"], "已结束 tictactoe 会话");
+
+    let edit = mock
+        .wait_for_call("editMessageText", |call| {
+            call.params["message_id"].as_i64() == Some(board_id)
+        })
+        .await;
+    assert!(edit.params["text"]
+        .as_str()
+        .unwrap()
+        .ends_with("游戏已超时"));
+    assert!(edit.params.get("reply_markup").is_none());
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn admin_broadcast_keeps_text_verbatim() {
+    let mock = MockApi::start();
+    let bot = TestBot::start_with(
+        &mock,
+        &[
+            "--owner",
+            &ALICE.to_string(),
+            "--broadcast-chats",
+            &GROUP.to_string(),
+        ],
+    )
+    .await;
+
+    let text = "broadcast  \"test\"\n  line two";
+    mock.send_message(
+        ALICE,
+        ALICE,
+        &format!("/admin@{BOT_USERNAME} broadcast {text}"),
+    );
+
+    let sent = mock
+        .wait_for_call("sendMessage", |call| call.params["chat_id"] == GROUP)
+        .await;
+    assert_eq!(sent.params["text"], text);
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn admin_access_rules_require_persistence() {
+    let mock = MockApi::start();
+    let bot = TestBot::start_with(&mock, &["--owner", &ALICE.to_string()]).await;
+
+    let msg_id = mock.send_message(ALICE, ALICE, &format!("/admin deny user {BOB}"));
+
+    let reply = mock
+        .wait_for_call("sendMessage", |call| {
+            call.params["reply_to_message_id"].as_i64() == Some(msg_id)
+        })
+        .await;
+    assert!(reply.params["text"]
+        .as_str()
+        .unwrap()
+        .contains("state_file"));
+
+    bot.stop().await;
+
+    let state_path = env::temp_dir().join(format!("eaimty_bot_test_{}.json", process::id()));
+
+    let mock = MockApi::start();
+    let bot = TestBot::start_with(
+        &mock,
+        &[
+            "--owner",
+            &ALICE.to_string(),
+            "--state-file",
+            state_path.to_str().unwrap(),
+        ],
+    )
+    .await;
+
+    let msg_id = mock.send_message(ALICE, ALICE, &format!("/admin deny user {BOB}"));
+
+    let reply = mock
+        .wait_for_call("sendMessage", |call| {
+            call.params["reply_to_message_id"].as_i64() == Some(msg_id)
+        })
+        .await;
+    assert_eq!(reply.params["text"], format!("已设置 deny user {BOB}"));
+
+    bot.stop().await;
+    let _ = fs::remove_file(&state_path);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn dice_keyword_sends_dice() {
+    let mock = MockApi::start();
+    let bot = TestBot::start(&mock).await;
+
+    let msg_id = mock.send_message(ALICE, ALICE, "掷个骰子");
+
+    let dice = mock.wait_for_calls("sendDice", 1).await;
+    assert_eq!(dice[0].params["chat_id"], ALICE);
+    assert_eq!(dice[0].params["reply_to_message_id"], msg_id);
+    assert_eq!(dice[0].params["emoji"], "🎲");
+
+    bot.stop().await;
+}
+
+#[test]
+fn every_command_has_text_in_every_catalog() {
+    Handler::check_command_texts().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn help_lists_commands() {
+    let mock = MockApi::start();
+    let bot = TestBot::start(&mock).await;
+
+    mock.send_message(ALICE, ALICE, "/help");
+
+    let reply = mock.wait_for_calls("sendMessage", 1).await;
+    let reply_id = reply[0].result["message_id"].as_i64().unwrap();
+    let help = mock.get_message_text(ALICE, reply_id).unwrap();
+
+    assert!(help.contains("/tictactoe - 玩 Tic-Tac-Toe"));
+    assert!(!help.contains("/admin"));
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn help_lists_commands_for_chat_type() {
+    let mock = MockApi::start();
+    let bot = TestBot::start(&mock).await;
+
+    mock.send_message(ALICE, ALICE, "/help");
+    mock.send_message(GROUP, ALICE, &format!("/help@{BOT_USERNAME}"));
+
+    let replies = mock.wait_for_calls("sendMessage", 2).await;
+    let get_help = |chat_id| {
+        let reply = replies
+            .iter()
+            .find(|reply| reply.params["chat_id"].as_i64() == Some(chat_id))
+            .unwrap();
+        let reply_id = reply.result["message_id"].as_i64().unwrap();
+        mock.get_message_text(chat_id, reply_id).unwrap()
+    };
+
+    assert!(!get_help(ALICE).contains("/settings"));
+    assert!(get_help(GROUP).contains("/settings"));
+
+    mock.send_message(ALICE, ALICE, "/help settings");
+
+    let reply = mock.wait_for_calls("sendMessage", 3).await;
+    let reply_id = reply[2].result["message_id"].as_i64().unwrap();
+    let help = mock.get_message_text(ALICE, reply_id).unwrap();
+
+    assert!(!help.starts_with("/settings"));
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn minesweeper_rejects_empty_board() {
+    let mock = MockApi::start();
+    let bot = TestBot::start(&mock).await;
+
+    mock.send_message(ALICE, ALICE, "/minesweeper 0 5 0");
+    mock.send_message(ALICE, ALICE, "/minesweeper 5 0 0");
+
+    let replies = mock.wait_for_calls("sendMessage", 2).await;
+
+    for reply in replies {
+        assert_eq!(reply.params["text"], "参数错误");
+    }
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn send_message_is_not_retried_after_server_error() {
+    let mock = MockApi::start();
+    let bot = TestBot::start(&mock).await;
+
+    mock.fail_next("sendMessage", StatusCode::BAD_GATEWAY, "Bad Gateway");
+    mock.send_message(ALICE, ALICE, "/help");
+    mock.wait_for_failures("sendMessage").await;
+
+    let msg_id = mock.send_message(ALICE, ALICE, "/start");
+    mock.wait_for_call("sendMessage", |call| {
+        call.params["reply_to_message_id"].as_i64() == Some(msg_id)
+    })
+    .await;
+
+    assert_eq!(mock.get_calls("sendMessage").len(), 1);
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn retry_after_counts_against_retry_budget() {
+    let mock = MockApi::start();
+    let bot = TestBot::start_with(&mock, &["--api-retry-budget", "2"]).await;
+
+    mock.rate_limit_next("sendMessage", 1);
+    let msg_id = mock.send_message(ALICE, ALICE, "/help");
+    mock.wait_for_call("sendMessage", |call| {
+        call.params["reply_to_message_id"].as_i64() == Some(msg_id)
+    })
+    .await;
+
+    let start = Instant::now();
+
+    mock.rate_limit_next("sendMessage", 5);
+    mock.send_message(ALICE, ALICE, "/help");
+    mock.wait_for_failures("sendMessage").await;
+
+    let msg_id = mock.send_message(ALICE, ALICE, "/start");
+    mock.wait_for_call("sendMessage", |call| {
+        call.params["reply_to_message_id"].as_i64() == Some(msg_id)
+    })
+    .await;
+
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(mock.get_calls("sendMessage").len(), 2);
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn leaves_denied_group_when_added() {
+    let mock = MockApi::start();
+    let bot = TestBot::start_with(
+        &mock,
+        &["--deny-chats", &GROUP.to_string(), "--leave-denied-groups"],
+    )
+    .await;
+
+    mock.add_bot_to_chat(GROUP, ALICE);
+
+    let leave = mock.wait_for_calls("leaveChat", 1).await;
+    assert_eq!(leave[0].params["chat_id"], GROUP);
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn long_polling_deletes_webhook() {
+    let mock = MockApi::start();
+    let bot = TestBot::start(&mock).await;
+
+    mock.wait_for_calls("deleteWebhook", 1).await;
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn long_polling_confirms_offset_on_shutdown() {
+    let mock = MockApi::start();
+    let bot = TestBot::start(&mock).await;
+
+    let msg_id = mock.send_message(ALICE, ALICE, "/help");
+    mock.wait_for_call("sendMessage", |call| {
+        call.params["reply_to_message_id"].as_i64() == Some(msg_id)
+    })
+    .await;
+
+    bot.stop().await;
+
+    let calls = mock.get_calls("getUpdates");
+    let last = calls.last().unwrap();
+    assert_eq!(last.params["offset"], 2);
+    assert_eq!(last.params["timeout"], 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn webhook_mode_sets_webhook() {
+    let cert_path = env::temp_dir().join(format!("eaimty_bot_test_{}.pem", process::id()));
+    fs::write(&cert_path, "CERTIFICATE").unwrap();
+
+    let mock = MockApi::start();
+    let port = get_free_port();
+
+    let bot = TestBot::start_with(
+        &mock,
+        &[
+            "--webhook-port",
+            &port.to_string(),
+            "--webhook-url",
+            "https://example.com/webhook",
+            "--webhook-max-connections",
+            "10",
+            "--webhook-self-signed-cert",
+            cert_path.to_str().unwrap(),
+        ],
+    )
+    .await;
+
+    let set_webhook = mock.wait_for_calls("setWebhook", 1).await;
+    assert_eq!(set_webhook[0].params["url"], "https://example.com/webhook");
+    assert_eq!(set_webhook[0].params["max_connections"], "10");
+    assert_eq!(
+        set_webhook[0].params["allowed_updates"],
+        r#"["message","callback_query","my_chat_member"]"#
+    );
+    assert_eq!(set_webhook[0].params["certificate"], "CERTIFICATE");
+
+    let secret_token = set_webhook[0].params["secret_token"].as_str().unwrap();
+    assert_eq!(secret_token.len(), 32);
+
+    let update = mock.create_message_update(ALICE, ALICE, "/help");
+    assert_eq!(
+        post_update(port, "/webhook", None, &update).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        post_update(port, "/webhook", Some("wrong"), &update).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        post_update(port, "/", Some(secret_token), &update).await,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        post_update(port, "/webhook", Some(secret_token), &update).await,
+        StatusCode::OK
+    );
+
+    mock.wait_for_calls("sendMessage", 1).await;
+
+    bot.stop().await;
+    fs::remove_file(&cert_path).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn set_webhook_retries_server_errors() {
+    let mock = MockApi::start();
+    let port = get_free_port();
+
+    mock.fail_next("setWebhook", StatusCode::BAD_GATEWAY, "Bad Gateway");
+    mock.fail_next(
+        "setWebhook",
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Internal Server Error",
+    );
+
+    let bot = TestBot::start_with(
+        &mock,
+        &[
+            "--webhook-listen",
+            &format!("127.0.0.1:{port}"),
+            "--webhook-url",
+            "https://example.com/webhook",
+        ],
+    )
+    .await;
+
+    let set_webhook = mock.wait_for_calls("setWebhook", 1).await;
+    assert_eq!(set_webhook[0].params["url"], "https://example.com/webhook");
+
+    bot.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn webhook_listen_with_custom_path_and_secret() {
+    let mock = MockApi::start();
+    let port = get_free_port();
+
+    let bot = TestBot::start_with(
+        &mock,
+        &[
+            "--webhook-listen",
+            &format!("127.0.0.1:{port}"),
+            "--webhook-path",
+            "/updates",
+            "--webhook-secret-token",
+            "my-secret_token",
+        ],
+    )
+    .await;
+
+    let update = mock.create_message_update(ALICE, ALICE, "/help");
+    assert_eq!(
+        post_update(port, "/updates", Some("other-secret"), &update).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        post_update(port, "/updates", Some("my-secret_token"), &update).await,
+        StatusCode::OK
+    );
+
+    mock.wait_for_calls("sendMessage", 1).await;
+
+    bot.stop().await;
+    assert!(mock.get_calls("setWebhook").is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn webhook_rejects_oversized_requests() {
+    let mock = MockApi::start();
+    let port = get_free_port();
+
+    let bot = TestBot::start_with(
+        &mock,
+        &[
+            "--webhook-listen",
+            &format!("127.0.0.1:{port}"),
+            "--webhook-secret-token",
+            "secret",
+        ],
+    )
+    .await;
+
+    let mut update = mock.create_message_update(ALICE, ALICE, "/help");
+    assert_eq!(
+        post_update(port, "/", Some("secret"), &update).await,
+        StatusCode::OK
+    );
+    mock.wait_for_calls("sendMessage", 1).await;
+
+    update["padding"] = Value::String("x".repeat(2 * 1024 * 1024));
+    assert_eq!(
+        post_update(port, "/", Some("secret"), &update).await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+
+    bot.stop().await;
+    assert_eq!(mock.get_calls("sendMessage").len(), 1);
+}
+
+#[test]
+fn webhook_listen_flag_overrides_port_in_config_file() {
+    let config_path = env::temp_dir().join(format!("eaimty_bot_test_{}.toml", process::id()));
+    fs::write(
+        &config_path,
+        "[webhook]\nport = 8443\nsecret_token = \"secret\"\n",
+    )
+    .unwrap();
+
+    let config_arg = config_path.to_str().unwrap();
+
+    let parse = |extra_args: &[&str]| {
+        let args = ["eaimty_bot", "--token", TOKEN, "--config", config_arg]
+            .iter()
+            .chain(extra_args)
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>();
+
+        ConfigBuilder::new().parse(&args)
+    };
+
+    let config = parse(&[]).unwrap();
+    assert_eq!(config.webhook_listen.unwrap().to_string(), "0.0.0.0:8443");
+
+    let config = parse(&["--webhook-listen", "[::1]:8080"]).unwrap();
+    assert_eq!(config.webhook_listen.unwrap().to_string(), "[::1]:8080");
+
+    assert!(parse(&["--webhook-listen", "[::1]:8080", "--webhook-port", "80"]).is_err());
+
+    fs::remove_file(&config_path).unwrap();
+}
+
+#[test]
+fn webhook_listen_requires_secret_token_without_url() {
+    let parse = |extra_args: &[&str]| {
+        let args = [
+            "eaimty_bot",
+            "--token",
+            TOKEN,
+            "--webhook-listen",
+            "127.0.0.1:8443",
+        ]
+        .iter()
+        .chain(extra_args)
+        .map(|arg| arg.to_string())
+        .collect::<Vec<_>>();
+
+        ConfigBuilder::new().parse(&args)
+    };
+
+    assert!(parse(&[]).is_err());
+    assert!(parse(&["--webhook-secret-token", "secret"]).is_ok());
+    assert!(parse(&["--webhook-url", "https://example.com/webhook"]).is_ok());
+}
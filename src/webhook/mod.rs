@@ -0,0 +1,259 @@
+use self::tls::CertResolver;
+use crate::{Config, Handler};
+use anyhow::{anyhow, Error, Result};
+use hyper::{
+    body::HttpBody, header::CONTENT_LENGTH, server::conn::Http, service::service_fn, Body, Method,
+    Request, Response, StatusCode,
+};
+use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
+use std::{
+    convert::Infallible,
+    fmt::{Display, Formatter, Result as FmtResult},
+    net::SocketAddr,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use tgbot::{types::Update, UpdateHandler};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpListener,
+    time,
+};
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, warn};
+
+#[cfg(unix)]
+use std::{fs, os::unix::fs::FileTypeExt, path::PathBuf};
+
+#[cfg(unix)]
+use tokio::net::UnixListener;
+
+mod tls;
+
+const SECRET_TOKEN_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+const SECRET_TOKEN_LEN: usize = 32;
+const ACCEPT_ERROR_TIMEOUT: Duration = Duration::from_secs(1);
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenAddr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        #[cfg(unix)]
+        if let Some(path) = s.strip_prefix("unix:").filter(|path| !path.is_empty()) {
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+
+        s.parse()
+            .map(Self::Tcp)
+            .map_err(|_| anyhow!("Invalid listen address: {s} (supported: IP:PORT, unix:PATH)"))
+    }
+}
+
+impl Display for ListenAddr {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::Tcp(addr) => write!(f, "{addr}"),
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+pub fn generate_secret_token() -> String {
+    OsRng
+        .sample_iter(&Alphanumeric)
+        .take(SECRET_TOKEN_LEN)
+        .map(char::from)
+        .collect()
+}
+
+struct Webhook {
+    path: String,
+    secret_token: String,
+    handler: Handler,
+}
+
+pub async fn run_server(
+    config: &Config,
+    listen: &ListenAddr,
+    secret_token: String,
+    handler: Handler,
+) -> Result<()> {
+    let acceptor = if let (Some(cert), Some(key)) = (&config.webhook_cert, &config.webhook_key) {
+        let resolver = Arc::new(CertResolver::new(cert.clone(), key.clone())?);
+        tokio::spawn(resolver.clone().reload_periodically());
+
+        Some(TlsAcceptor::from(resolver.get_server_config()))
+    } else {
+        None
+    };
+
+    let webhook = Arc::new(Webhook {
+        path: config.webhook_path.clone(),
+        secret_token,
+        handler,
+    });
+
+    match listen {
+        ListenAddr::Tcp(addr) => {
+            let listener = TcpListener::bind(addr)
+                .await
+                .map_err(|err| anyhow!("Failed to listen on {listen} for webhook: {err}"))?;
+
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve_connection(stream, acceptor.clone(), webhook.clone()));
+                    }
+                    Err(err) => {
+                        warn!(error = %err, "Failed to accept webhook connection");
+                        time::sleep(ACCEPT_ERROR_TIMEOUT).await;
+                    }
+                }
+            }
+        }
+        #[cfg(unix)]
+        ListenAddr::Unix(path) => {
+            if fs::metadata(path).map_or(false, |meta| meta.file_type().is_socket()) {
+                fs::remove_file(path)
+                    .map_err(|err| anyhow!("Failed to remove stale socket {listen}: {err}"))?;
+            }
+
+            let listener = UnixListener::bind(path)
+                .map_err(|err| anyhow!("Failed to listen on {listen} for webhook: {err}"))?;
+
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve_connection(stream, acceptor.clone(), webhook.clone()));
+                    }
+                    Err(err) => {
+                        warn!(error = %err, "Failed to accept webhook connection");
+                        time::sleep(ACCEPT_ERROR_TIMEOUT).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn serve_connection<S>(stream: S, acceptor: Option<TlsAcceptor>, webhook: Arc<Webhook>)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let service = service_fn(move |req| handle_request(webhook.clone(), req));
+
+    let res = if let Some(acceptor) = acceptor {
+        match time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+            Ok(Ok(stream)) => Http::new().serve_connection(stream, service).await,
+            Ok(Err(err)) => {
+                debug!(error = %err, "TLS handshake failed");
+                return;
+            }
+            Err(_) => {
+                debug!("TLS handshake timed out");
+                return;
+            }
+        }
+    } else {
+        Http::new().serve_connection(stream, service).await
+    };
+
+    if let Err(err) = res {
+        debug!(error = %err, "Webhook connection failed");
+    }
+}
+
+async fn handle_request(
+    webhook: Arc<Webhook>,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    if req.method() != Method::POST {
+        return Ok(status(StatusCode::METHOD_NOT_ALLOWED));
+    }
+
+    if req.uri().path() != webhook.path {
+        return Ok(status(StatusCode::NOT_FOUND));
+    }
+
+    let is_valid = req
+        .headers()
+        .get(SECRET_TOKEN_HEADER)
+        .map_or(false, |token| {
+            is_same_secret(token.as_bytes(), webhook.secret_token.as_bytes())
+        });
+
+    if !is_valid {
+        warn!("Rejected webhook request with an invalid secret token");
+        return Ok(status(StatusCode::UNAUTHORIZED));
+    }
+
+    let content_length = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok())
+        .and_then(|len| len.parse::<u64>().ok());
+
+    if content_length.map_or(false, |len| len > MAX_BODY_SIZE as u64) {
+        warn!("Rejected an oversized webhook request");
+        return Ok(status(StatusCode::PAYLOAD_TOO_LARGE));
+    }
+
+    let body = match read_body(req.into_body()).await {
+        Ok(Some(body)) => body,
+        Ok(None) => {
+            warn!("Rejected an oversized webhook request");
+            return Ok(status(StatusCode::PAYLOAD_TOO_LARGE));
+        }
+        Err(err) => {
+            debug!(error = %err, "Failed to read webhook request");
+            return Ok(status(StatusCode::BAD_REQUEST));
+        }
+    };
+
+    match serde_json::from_slice::<Update>(&body) {
+        Ok(update) => {
+            webhook.handler.handle(update).await;
+            Ok(status(StatusCode::OK))
+        }
+        Err(err) => {
+            warn!(error = %err, "Failed to parse update");
+            Ok(status(StatusCode::BAD_REQUEST))
+        }
+    }
+}
+
+async fn read_body(mut body: Body) -> Result<Option<Vec<u8>>, hyper::Error> {
+    let mut buf = Vec::new();
+
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+
+        if buf.len() + chunk.len() > MAX_BODY_SIZE {
+            return Ok(None);
+        }
+
+        buf.extend_from_slice(&chunk);
+    }
+
+    Ok(Some(buf))
+}
+
+fn is_same_secret(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn status(code: StatusCode) -> Response<Body> {
+    let mut res = Response::new(Body::empty());
+    *res.status_mut() = code;
+    res
+}